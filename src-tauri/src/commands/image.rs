@@ -1,9 +1,9 @@
-use std::fs;
 use std::path::Path;
 use tauri::AppHandle;
 
-use super::projects::filesystem::ensure_dir;
+use super::projects::filesystem::{ensure_dir, write_atomic};
 use super::projects::paths::ProjectPaths;
+use super::projects::recycle;
 
 pub fn get_image_path(project_path: &Path, image_name: &str) -> Result<Option<String>, String> {
     let paths = ProjectPaths::new(project_path);
@@ -32,7 +32,7 @@ pub async fn save_image(
     let images_dir = paths.images_dir();
     ensure_dir(&images_dir)?;
     let image_path = paths.image_file(&image_name);
-    fs::write(&image_path, bytes).map_err(|e| format!("Failed to write image: {}", e))?;
+    write_atomic(&image_path, bytes)?;
 
     Ok(image_path.to_string_lossy().to_string())
 }
@@ -55,10 +55,10 @@ pub async fn delete_image(
 ) -> Result<(), String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
 
-    // Delete image file
+    // Move image file to the recycle area
     let image_path = paths.image_file(&image_name);
     if image_path.exists() {
-        fs::remove_file(&image_path).map_err(|e| format!("Failed to delete image: {}", e))?;
+        recycle::recycle_project_file(paths.root(), "image", &image_path, None)?;
     }
 
     Ok(())
@@ -94,8 +94,7 @@ pub async fn resize_image(
             .map_err(|e| format!("Failed to re-encode image: {}", e))?;
 
         // Overwrite the original file with resized version
-        std::fs::write(&image_path, buffer.into_inner())
-            .map_err(|e| format!("Failed to write resized image: {}", e))?;
+        write_atomic(Path::new(&image_path), buffer.into_inner())?;
     }
 
     Ok(())
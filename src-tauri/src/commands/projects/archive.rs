@@ -0,0 +1,551 @@
+//! Project archives: a whole project in one `.sixseven` file to hand to someone else
+//!
+//! An archive is a zip file with `manifest.json` at its root and the project's
+//! files under `files/`, laid out as in the project. The manifest lists every
+//! file with its size and SHA-256, which are checked on import. Metadata and
+//! editor state are written at export time, so referenced imports copied into
+//! the archive become ordinary imports in `videos/`.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::filesystem::{
+    collect_files, current_timestamp, editor_state_json, ensure_dir, file_fingerprint,
+    lock_project, project_meta_json, read_editor_state, read_project_meta, sanitize_project_name,
+    update_project_meta,
+};
+use super::history::relative_path;
+use super::paths::ProjectPaths;
+use super::storage::walk;
+use super::types::{ArchiveEntry, ArchiveManifest, ArchiveOptions, ArchiveSummary};
+
+pub const ARCHIVE_EXTENSION: &str = "sixseven";
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_PREFIX: &str = "files/";
+
+/// Extensions of formats that are already compressed and are stored as they are
+const COMPRESSED_EXTENSIONS: [&str; 10] = [
+    "mp4", "mov", "mkv", "webm", "m4v", "avi", "jpg", "jpeg", "png", "webp",
+];
+
+enum Source {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+fn is_compressed(key: &str) -> bool {
+    // Imports in videos/ are named by id, without an extension
+    key.starts_with("videos/")
+        || Path::new(key)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| COMPRESSED_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Copy `reader` to `writer`, returning the SHA-256 and length of the data
+fn copy_hashing(reader: &mut impl Read, writer: &mut impl Write) -> Result<(String, u64), String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer
+            .write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write: {}", e))?;
+        total += read as u64;
+    }
+    Ok((format!("{:x}", hasher.finalize()), total))
+}
+
+/// Write the project at `project_path` to an archive at `output`
+pub fn export_archive(
+    project_path: &Path,
+    project_name: &str,
+    output: &Path,
+    options: &ArchiveOptions,
+) -> Result<ArchiveSummary, String> {
+    // Writing gigabytes of media can take minutes, so the project is only
+    // locked while its metadata and editor state are read
+    let entries = {
+        let _lock = lock_project(project_path)?;
+        archive_entries(project_path, options)?
+    };
+
+    let result = write_archive(output, project_name, &entries);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+/// What to archive: metadata and editor state as written at export time, and
+/// the project files to copy
+fn archive_entries(
+    project_path: &Path,
+    options: &ArchiveOptions,
+) -> Result<Vec<(String, Source)>, String> {
+    let paths = ProjectPaths::new(project_path);
+    let mut meta = read_project_meta(project_path)?;
+    let mut state = read_editor_state(project_path)?;
+
+    let used: HashSet<PathBuf> = state
+        .iter()
+        .flat_map(|state| &state.clips)
+        .map(|clip| PathBuf::from(&clip.video_path))
+        .collect();
+    let wanted = |file: &Path| !options.timeline_media_only || used.contains(file);
+
+    // Media left out of the archive would leave records pointing at missing files
+    if options.timeline_media_only {
+        meta.videos
+            .retain(|video| wanted(&paths.video_file(&format!("{}.mp4", video.id))));
+        meta.imported_videos
+            .retain(|record| record.reference || wanted(Path::new(&record.file_path)));
+    }
+
+    let mut entries: Vec<(String, Source)> = Vec::new();
+
+    if options.include_referenced_media {
+        for record in meta.imported_videos.iter_mut().filter(|r| r.reference) {
+            let source = PathBuf::from(&record.file_path);
+            if !source.exists() || !wanted(&source) {
+                continue;
+            }
+
+            let copy = paths.video_file(&record.id).to_string_lossy().to_string();
+            let clips = state.iter_mut().flat_map(|state| state.clips.iter_mut());
+            for clip in clips.filter(|clip| clip.video_path == record.file_path) {
+                clip.video_path = copy.clone();
+            }
+            entries.push((
+                relative_path(project_path, Path::new(&copy)),
+                Source::File(source),
+            ));
+            record.reference = false;
+            record.file_path = copy;
+        }
+    }
+
+    entries.push((
+        relative_path(project_path, &paths.metadata_file()),
+        Source::Bytes(project_meta_json(project_path, &meta)?.into_bytes()),
+    ));
+    if let Some(state) = &state {
+        entries.push((
+            relative_path(project_path, &paths.editor_state_file()),
+            Source::Bytes(editor_state_json(project_path, state)?.into_bytes()),
+        ));
+    }
+
+    // Directories to copy, and whether they hold media subject to `timeline_media_only`
+    let mut dirs = vec![
+        (paths.storyboard_dir(), false),
+        (paths.images_dir(), false),
+        (paths.branding_dir(), false),
+        (paths.videos_dir(), true),
+    ];
+    if options.include_cache {
+        dirs.push((paths.cache_dir(), false));
+    }
+    for (dir, media) in dirs {
+        if !dir.exists() {
+            continue;
+        }
+        let mut files = Vec::new();
+        collect_files(&dir, &mut files)?;
+        for file in files {
+            if media && !wanted(&file) {
+                continue;
+            }
+            entries.push((relative_path(project_path, &file), Source::File(file)));
+        }
+    }
+
+    // Versions keep their editor state under a hidden `.sixseven/`, which
+    // `collect_files` leaves out
+    if options.include_versions && paths.versions_dir().exists() {
+        let mut files = Vec::new();
+        walk(&paths.versions_dir(), &mut files)?;
+        for (file, _) in files {
+            entries.push((relative_path(project_path, &file), Source::File(file)));
+        }
+    }
+
+    Ok(entries)
+}
+
+fn write_archive(
+    output: &Path,
+    project_name: &str,
+    entries: &[(String, Source)],
+) -> Result<ArchiveSummary, String> {
+    let file = File::create(output).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let mut files = Vec::new();
+
+    for (key, source) in entries {
+        let (mut reader, size): (Box<dyn Read>, u64) = match source {
+            Source::File(path) => {
+                let file = File::open(path)
+                    .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+                let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+                (Box::new(file), size)
+            }
+            Source::Bytes(bytes) => (Box::new(bytes.as_slice()), bytes.len() as u64),
+        };
+
+        let method = if is_compressed(key) {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        let file_options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(size >= u32::MAX as u64);
+        zip.start_file(format!("{}{}", FILES_PREFIX, key), file_options)
+            .map_err(|e| format!("Failed to add {} to archive: {}", key, e))?;
+
+        let (sha256, size) = copy_hashing(&mut reader, &mut zip)
+            .map_err(|e| format!("Failed to add {} to archive: {}", key, e))?;
+        files.push(ArchiveEntry {
+            path: key.clone(),
+            size,
+            sha256,
+        });
+    }
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        project_name: project_name.to_string(),
+        exported_at: current_timestamp(),
+        files,
+    };
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize archive manifest: {}", e))?;
+    zip.start_file(MANIFEST_FILE, SimpleFileOptions::default())
+        .and_then(|_| Ok(zip.write_all(json.as_bytes())?))
+        .map_err(|e| format!("Failed to write archive manifest: {}", e))?;
+    zip.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    Ok(ArchiveSummary {
+        path: output.to_string_lossy().to_string(),
+        file_count: manifest.files.len(),
+        total_bytes: manifest.files.iter().map(|f| f.size).sum(),
+    })
+}
+
+/// `name`, or `name 2`, `name 3`, ... if a project with that name exists
+pub(crate) fn unique_project_name(workspace: &Path, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut counter = 2;
+    while workspace.join(&candidate).exists() {
+        candidate = format!("{} {}", name, counter);
+        counter += 1;
+    }
+    candidate
+}
+
+/// Where an archived file goes. Archives come from elsewhere, so only plain
+/// relative paths are accepted.
+fn entry_path(root: &Path, key: &str) -> Result<PathBuf, String> {
+    let mut path = root.to_path_buf();
+    for part in key.split('/') {
+        let unsafe_part = part.is_empty()
+            || part == "."
+            || part == ".."
+            || part.contains(['\\', ':'])
+            || Path::new(part).is_absolute();
+        if unsafe_part {
+            return Err(format!("Archive contains an unsafe path: {}", key));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+/// Unpack an archive into `workspace` as a new project, returning its name and path.
+///
+/// Files are unpacked next to the workspace's projects and only moved into
+/// place once every file has been checked against the manifest.
+pub fn import_archive(
+    archive_path: &Path,
+    workspace: &Path,
+    name: Option<&str>,
+) -> Result<(String, PathBuf), String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| format!("Failed to open archive: {}", e))?;
+
+    let manifest: ArchiveManifest = {
+        let mut entry = zip
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| "Not a project archive: manifest.json is missing".to_string())?;
+        let mut json = String::new();
+        entry
+            .read_to_string(&mut json)
+            .map_err(|e| format!("Failed to read archive manifest: {}", e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse archive manifest: {}", e))?
+    };
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(format!(
+            "The archive was made by a newer version of the app (format {}, this version supports {})",
+            manifest.format_version, ARCHIVE_FORMAT_VERSION
+        ));
+    }
+
+    let name = sanitize_project_name(name.unwrap_or(&manifest.project_name))?;
+    let name = unique_project_name(workspace, &name);
+    let project_path = workspace.join(&name);
+    let staging = workspace.join(format!(".{}.importing", name));
+    if staging.exists() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+
+    let unpacked = extract(&mut zip, &manifest, &staging).and_then(|_| {
+        fs::rename(&staging, &project_path)
+            .map_err(|e| format!("Failed to move imported project into place: {}", e))
+    });
+    if let Err(e) = unpacked {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    // Copied imports have new modification times; record them so they are not reported as changed
+    update_project_meta(&project_path, |meta| {
+        for record in meta.imported_videos.iter_mut().filter(|r| !r.reference) {
+            if let Ok((size, modified_at)) = file_fingerprint(Path::new(&record.file_path)) {
+                record.size = size;
+                record.modified_at = modified_at;
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok((name, project_path))
+}
+
+fn extract(
+    zip: &mut ZipArchive<File>,
+    manifest: &ArchiveManifest,
+    target: &Path,
+) -> Result<(), String> {
+    for entry in &manifest.files {
+        let dest = entry_path(target, &entry.path)?;
+        let mut file = zip
+            .by_name(&format!("{}{}", FILES_PREFIX, entry.path))
+            .map_err(|_| format!("Archive is missing {}", entry.path))?;
+
+        if let Some(parent) = dest.parent() {
+            ensure_dir(parent)?;
+        }
+        let mut out = File::create(&dest)
+            .map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+        let (sha256, size) = copy_hashing(&mut file, &mut out)
+            .map_err(|e| format!("Failed to unpack {}: {}", entry.path, e))?;
+
+        if sha256 != entry.sha256 || size != entry.size {
+            return Err(format!("{} is damaged in the archive", entry.path));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::migrations::PROJECT_META;
+    use super::super::test_support::{clip_json, TempProject};
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn open(path: &Path) -> (ZipArchive<File>, ArchiveManifest) {
+        let mut zip = ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let mut json = String::new();
+        zip.by_name(MANIFEST_FILE)
+            .unwrap()
+            .read_to_string(&mut json)
+            .unwrap();
+        (zip, serde_json::from_str(&json).unwrap())
+    }
+
+    fn read_entry(zip: &mut ZipArchive<File>, key: &str) -> String {
+        let mut contents = String::new();
+        zip.by_name(&format!("{}{}", FILES_PREFIX, key))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    /// An archive of two small files, written into `project`
+    fn small_archive(project: &TempProject) -> PathBuf {
+        let output = project.root().join("test.sixseven");
+        let entries = [
+            (
+                "storyboard/context.md".to_string(),
+                Source::Bytes(b"Noir".to_vec()),
+            ),
+            ("images/a.jpg".to_string(), Source::Bytes(vec![1, 2, 3])),
+        ];
+        write_archive(&output, "Film", &entries).unwrap();
+        output
+    }
+
+    #[test]
+    fn entry_paths_stay_inside_the_target() {
+        let root = Path::new("target");
+        assert_eq!(
+            entry_path(root, "storyboard/scenes/index.json").unwrap(),
+            root.join("storyboard").join("scenes").join("index.json")
+        );
+        // Hidden folders such as `.sixseven` are part of every project
+        assert!(entry_path(root, ".sixseven/metadata.json").is_ok());
+    }
+
+    #[test]
+    fn entry_paths_reject_escapes_and_absolute_paths() {
+        let root = Path::new("target");
+        for key in [
+            "../outside.txt",
+            "videos/../../outside.txt",
+            "./videos/a",
+            "/etc/passwd",
+            "videos//a",
+            "C:/Windows/system.ini",
+            "C:outside.txt",
+            "videos\\..\\..\\outside.txt",
+            "",
+        ] {
+            assert!(entry_path(root, key).is_err(), "accepted {:?}", key);
+        }
+    }
+
+    #[test]
+    fn extract_unpacks_files_that_match_the_manifest() {
+        let project = TempProject::new();
+        let (mut zip, manifest) = open(&small_archive(&project));
+        let target = project.root().join("unpacked");
+
+        extract(&mut zip, &manifest, &target).unwrap();
+
+        let context = target.join("storyboard").join("context.md");
+        assert_eq!(fs::read_to_string(context).unwrap(), "Noir");
+        assert_eq!(
+            fs::read(target.join("images").join("a.jpg")).unwrap(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn extract_rejects_files_whose_hash_or_size_differ() {
+        let project = TempProject::new();
+        let (mut zip, manifest) = open(&small_archive(&project));
+
+        let mut wrong_hash = manifest.clone();
+        wrong_hash.files[0].sha256 = format!("{:x}", Sha256::digest(b"Comedy"));
+        let error = extract(&mut zip, &wrong_hash, &project.root().join("a")).unwrap_err();
+        assert!(error.contains("damaged"), "{}", error);
+
+        let mut wrong_size = manifest.clone();
+        wrong_size.files[1].size += 1;
+        let error = extract(&mut zip, &wrong_size, &project.root().join("b")).unwrap_err();
+        assert!(error.contains("damaged"), "{}", error);
+    }
+
+    #[test]
+    fn extract_rejects_unsafe_or_missing_entries() {
+        let project = TempProject::new();
+        let (mut zip, manifest) = open(&small_archive(&project));
+        let target = project.root().join("unpacked");
+
+        let mut escaping = manifest.clone();
+        escaping.files[0].path = "../escaped.md".to_string();
+        assert!(extract(&mut zip, &escaping, &target).is_err());
+        assert!(!project.root().join("escaped.md").exists());
+
+        let mut missing = manifest.clone();
+        missing.files[0].path = "storyboard/other.md".to_string();
+        let error = extract(&mut zip, &missing, &target).unwrap_err();
+        assert!(error.contains("missing"), "{}", error);
+    }
+
+    #[test]
+    fn timeline_media_only_drops_records_of_media_left_out() {
+        let project = TempProject::new();
+        let video = |id: &str| {
+            json!({
+                "id": id,
+                "prompt": "",
+                "model": "sora-2",
+                "resolution": "1280x720",
+                "duration": 4,
+                "created_at": 0,
+            })
+        };
+        let import = |id: &str| {
+            json!({
+                "id": id,
+                "file_path": format!("videos/{}", id),
+                "reference": false,
+                "size": 1,
+                "modified_at": 0,
+                "hash": "",
+            })
+        };
+        let meta = json!({
+            "schema_version": PROJECT_META.current(),
+            "videos": [video("used"), video("unused")],
+            "imported_videos": [import("imported_used"), import("imported_unused")],
+            "created_at": 0,
+        });
+        project.write(project.metadata_file(), meta.to_string());
+        for name in ["used.mp4", "unused.mp4", "imported_used", "imported_unused"] {
+            project.write(project.video_file(name), b"video");
+        }
+        project.write_clips(vec![
+            clip_json("a", "videos/used.mp4", 0.0, 0.0, 4.0),
+            clip_json("b", "videos/imported_used", 4.0, 0.0, 4.0),
+        ]);
+
+        let output = project.root().join("test.sixseven");
+        let options = ArchiveOptions {
+            timeline_media_only: true,
+            ..Default::default()
+        };
+        export_archive(project.root(), "Film", &output, &options).unwrap();
+
+        let (mut zip, manifest) = open(&output);
+        let mut videos: Vec<&str> = manifest
+            .files
+            .iter()
+            .filter_map(|entry| entry.path.strip_prefix("videos/"))
+            .collect();
+        videos.sort();
+        assert_eq!(videos, ["imported_used", "used.mp4"]);
+
+        let meta: Value =
+            serde_json::from_str(&read_entry(&mut zip, ".sixseven/metadata.json")).unwrap();
+        let ids = |key: &str| -> Vec<String> {
+            meta[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|record| record["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(ids("videos"), ["used"]);
+        assert_eq!(ids("imported_videos"), ["imported_used"]);
+    }
+}
@@ -1,14 +1,37 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
 
+use super::archive::{self, ARCHIVE_EXTENSION};
 use super::filesystem::{
-    current_timestamp, ensure_dir, list_project_directories, read_project_meta,
-    sanitize_project_name, write_project_meta,
+    current_timestamp, ensure_dir, file_fingerprint, hash_file, list_project_directories,
+    lock_project, read_project_meta, read_recovery_log, sanitize_project_name, update_project_meta,
+    write_project_meta,
 };
+use super::history;
+use super::library::{self, normalize_tags};
+use super::lineage::build_lineage;
 use super::paths::ProjectPaths;
+use super::recycle;
+use super::relocate;
+use super::repair;
 use super::scenes;
-use super::types::{ProjectMeta, ProjectSummary, SceneDetails, SceneSummary, VideoMeta};
+use super::storage;
+use super::templates;
+use super::types::{
+    ArchiveOptions, ArchiveSummary, CleanupResult, DuplicateFile, DuplicateGroup, HistoryEntry,
+    HistoryLog, LibraryPage, LibraryQuery, ProjectDefaults, ProjectMeta, ProjectSummary,
+    ProjectTemplate, ProjectVersion, RecoveryEvent, RecycledItem, RecycledRecord, RemixLineage,
+    RepairReport, SceneDetails, SceneSummary, StorageReport, ValidationReport, VersionDiff,
+    VideoMeta, WorkspaceSummary,
+};
+use super::validation;
+use super::versions::{self, CURRENT_VERSION};
+use super::workspaces;
+use crate::commands::video_editor::ffmpeg::export_format;
+use crate::commands::video_editor::types::TimelineClip;
 
 #[tauri::command]
 pub async fn get_workspace_dir(app: AppHandle) -> Result<Option<String>, String> {
@@ -23,25 +46,73 @@ pub async fn ensure_workspace_exists(app: AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn list_projects(app: AppHandle) -> Result<Vec<ProjectSummary>, String> {
-    let ws = ProjectPaths::workspace(&app)?;
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<WorkspaceSummary>, String> {
+    workspaces::list_workspaces(&app)
+}
+
+/// Register a folder as a workspace
+///
+/// Opens a folder dialog when `path` is not given. The folder is created if
+/// it does not exist; it does not become the active workspace.
+#[tauri::command]
+pub async fn add_workspace(
+    app: AppHandle,
+    path: Option<String>,
+    name: Option<String>,
+) -> Result<WorkspaceSummary, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match app.dialog().file().blocking_pick_folder() {
+            Some(dir) => PathBuf::from(dir.to_string()),
+            None => return Err("Folder selection cancelled".to_string()),
+        },
+    };
+    workspaces::add_workspace(&app, &path, name.as_deref())
+}
+
+/// Unregister a workspace without touching its projects
+#[tauri::command]
+pub async fn remove_workspace(app: AppHandle, path: String) -> Result<(), String> {
+    workspaces::remove_workspace(&app, Path::new(&path))
+}
+
+#[tauri::command]
+pub async fn switch_workspace(app: AppHandle, path: String) -> Result<WorkspaceSummary, String> {
+    workspaces::switch_workspace(&app, Path::new(&path))
+}
+
+/// List projects in the active workspace, or with `all_workspaces` in every
+/// workspace whose folder can be found
+///
+/// Other commands find projects by name in the active workspace, so a project
+/// from another workspace opens after switching to its `workspace`.
+#[tauri::command]
+pub async fn list_projects(
+    app: AppHandle,
+    all_workspaces: Option<bool>,
+) -> Result<Vec<ProjectSummary>, String> {
+    let workspaces = if all_workspaces.unwrap_or(false) {
+        workspaces::list_workspaces(&app)?
+            .into_iter()
+            .map(|w| PathBuf::from(w.path))
+            .filter(|path| path.is_dir())
+            .collect()
+    } else {
+        vec![ProjectPaths::workspace(&app)?]
+    };
     let mut items: Vec<ProjectSummary> = Vec::new();
 
-    let directories = list_project_directories(&ws)?;
+    let mut directories = Vec::new();
+    for ws in &workspaces {
+        directories.extend(list_project_directories(ws)?);
+    }
 
     for (name, path) in directories {
-        let meta = read_project_meta(&path).unwrap_or_else(|_| ProjectMeta {
-            videos: Vec::new(),
-            path: path.to_string_lossy().to_string(),
-            created_at: current_timestamp(),
-            storyboard_response_id: None,
+        let meta = read_project_meta(&path).unwrap_or_else(|_| {
+            ProjectMeta::new(path.to_string_lossy().to_string(), current_timestamp())
         });
 
-        items.push(ProjectSummary {
-            name,
-            path: path.to_string_lossy().to_string(),
-            created_at: meta.created_at,
-        });
+        items.push(ProjectSummary::new(name, &path, meta.created_at));
     }
 
     // Sort by created_at descending (newest first)
@@ -49,32 +120,45 @@ pub async fn list_projects(app: AppHandle) -> Result<Vec<ProjectSummary>, String
     Ok(items)
 }
 
+/// Create a project, optionally starting from one of the workspace's templates
 #[tauri::command]
-pub async fn create_project(app: AppHandle, name: String) -> Result<ProjectSummary, String> {
+pub async fn create_project(
+    app: AppHandle,
+    name: String,
+    template: Option<String>,
+) -> Result<ProjectSummary, String> {
+    let workspace = ProjectPaths::workspace(&app)?;
+    let existed = workspace.join(sanitize_project_name(&name)?).exists();
+    if existed && template.is_some() {
+        return Err(format!("Project '{}' already exists", name.trim()));
+    }
     let paths = ProjectPaths::from_name_create_if_not_exists(&app, &name)?;
 
     let created_at = current_timestamp();
 
     // init metadata
-    ensure_dir(&paths.metadata_dir())?;
-    let meta_path = paths.metadata_file();
-    if !meta_path.exists() {
-        let json = serde_json::to_string_pretty(&ProjectMeta {
-            videos: Vec::new(),
-            path: paths.root().to_string_lossy().to_string(),
-            created_at,
-            storyboard_response_id: None,
-        })
-        .map_err(|e| e.to_string())?;
-        fs::write(&meta_path, json).map_err(|e| e.to_string())?;
+    if !paths.metadata_file().exists() {
+        let meta = ProjectMeta::new(paths.root().to_string_lossy().to_string(), created_at);
+        write_project_meta(paths.root(), &meta)?;
     }
-    Ok(ProjectSummary {
-        name: sanitize_project_name(&name)?,
-        path: paths.root().to_string_lossy().to_string(),
+    if let Some(template) = template {
+        if let Err(e) = templates::apply_template(paths.root(), &workspace, &template) {
+            let _ = fs::remove_dir_all(paths.root());
+            return Err(e);
+        }
+    }
+    Ok(ProjectSummary::new(
+        sanitize_project_name(&name)?,
+        paths.root(),
         created_at,
-    })
+    ))
 }
 
+/// Delete a project
+///
+/// By default, or with `mode` "recycle", the project moves to the workspace
+/// recycle area. "trash" uses the system trash instead and "delete" deletes it
+/// permanently.
 #[tauri::command]
 pub async fn delete_project(
     app: AppHandle,
@@ -92,14 +176,208 @@ pub async fn delete_project(
         Some("delete") => {
             fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete: {}", e))?
         }
-        _ => {
-            // move to trash
+        Some("trash") => {
             trash::delete(&dir).map_err(|e| format!("Failed to move to trash: {}", e))?;
         }
+        None | Some("recycle") => {
+            recycle::recycle_project(dir)?;
+        }
+        Some(mode) => return Err(format!("Unknown delete mode '{}'", mode)),
     }
     Ok(())
 }
 
+/// Items in the active workspace's recycle area, after purging expired ones
+#[tauri::command]
+pub async fn list_recycled_items(app: AppHandle) -> Result<Vec<RecycledItem>, String> {
+    let workspace = ProjectPaths::workspace(&app)?;
+    let retention_days = recycle::retention_days(&app)?;
+    recycle::purge_expired(&workspace, retention_days)?;
+    recycle::list_items(&workspace, retention_days)
+}
+
+/// Put a recycled item back where it was deleted from, returning its path
+#[tauri::command]
+pub async fn restore_recycled_item(app: AppHandle, item_id: String) -> Result<String, String> {
+    let workspace = ProjectPaths::workspace(&app)?;
+    let path = recycle::restore_item(&workspace, &item_id)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Permanently delete a recycled item, or everything in the recycle area when
+/// `item_id` is not given
+#[tauri::command]
+pub async fn purge_recycled_items(app: AppHandle, item_id: Option<String>) -> Result<(), String> {
+    let workspace = ProjectPaths::workspace(&app)?;
+    recycle::purge(&workspace, item_id.as_deref())
+}
+
+#[tauri::command]
+pub async fn get_recycle_retention(app: AppHandle) -> Result<u32, String> {
+    recycle::retention_days(&app)
+}
+
+/// Set how many days recycled items are kept; 0 keeps them until purged
+#[tauri::command]
+pub async fn set_recycle_retention(app: AppHandle, days: u32) -> Result<(), String> {
+    recycle::set_retention_days(&app, days)
+}
+
+/// Templates of the active workspace
+#[tauri::command]
+pub async fn list_project_templates(app: AppHandle) -> Result<Vec<ProjectTemplate>, String> {
+    let workspace = ProjectPaths::workspace(&app)?;
+    templates::list_templates(&workspace)
+}
+
+/// Save a project's storyboard, reference images, branding and defaults as a
+/// template of the active workspace
+#[tauri::command]
+pub async fn save_project_as_template(
+    app: AppHandle,
+    project_name: String,
+    template_name: String,
+    description: Option<String>,
+) -> Result<ProjectTemplate, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let workspace = ProjectPaths::workspace(&app)?;
+    templates::save_as_template(paths.root(), &workspace, &template_name, description)
+}
+
+#[tauri::command]
+pub async fn delete_project_template(app: AppHandle, template_name: String) -> Result<(), String> {
+    let workspace = ProjectPaths::workspace(&app)?;
+    templates::delete_template(&workspace, &template_name)
+}
+
+#[tauri::command]
+pub async fn get_project_defaults(
+    app: AppHandle,
+    project_name: String,
+) -> Result<ProjectDefaults, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    Ok(read_project_meta(paths.root())?.defaults)
+}
+
+/// Set the output format and transition used when an export or storyboard
+/// assembly does not specify its own
+#[tauri::command]
+pub async fn set_project_defaults(
+    app: AppHandle,
+    project_name: String,
+    mut defaults: ProjectDefaults,
+) -> Result<(), String> {
+    if let Some(format) = &defaults.output_format {
+        let format = export_format(format)
+            .ok_or_else(|| format!("Unsupported output format '{}'", format))?;
+        defaults.output_format = Some(format.to_string());
+    }
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    update_project_meta(paths.root(), |meta| {
+        meta.defaults = defaults;
+        Ok(())
+    })
+}
+
+/// Rename a project, updating the paths stored inside its files
+#[tauri::command]
+pub async fn rename_project(
+    app: AppHandle,
+    name: String,
+    new_name: String,
+) -> Result<ProjectSummary, String> {
+    let paths = ProjectPaths::from_name(&app, &name)?;
+    let new_name = sanitize_project_name(&new_name)?;
+    let new_path = ProjectPaths::workspace(&app)?.join(&new_name);
+
+    if new_path != paths.root() {
+        relocate::rename_project(paths.root(), &new_path)?;
+    }
+    let meta = read_project_meta(&new_path)?;
+
+    Ok(ProjectSummary::new(new_name, &new_path, meta.created_at))
+}
+
+/// Copy a project under a new name
+///
+/// With `link_media`, media files are hard-linked instead of copied to save space.
+#[tauri::command]
+pub async fn duplicate_project(
+    app: AppHandle,
+    name: String,
+    new_name: String,
+    link_media: Option<bool>,
+) -> Result<ProjectSummary, String> {
+    let paths = ProjectPaths::from_name(&app, &name)?;
+    let new_name = sanitize_project_name(&new_name)?;
+    let new_path = ProjectPaths::workspace(&app)?.join(&new_name);
+
+    relocate::duplicate_project(paths.root(), &new_path, link_media.unwrap_or(false))?;
+    let meta = read_project_meta(&new_path)?;
+
+    Ok(ProjectSummary::new(new_name, &new_path, meta.created_at))
+}
+
+/// Write the project to a single archive file for sharing
+///
+/// Opens a save dialog when `output_path` is not given.
+#[tauri::command]
+pub async fn export_project_archive(
+    app: AppHandle,
+    project_name: String,
+    output_path: Option<String>,
+    options: Option<ArchiveOptions>,
+) -> Result<ArchiveSummary, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let name = sanitize_project_name(&project_name)?;
+
+    let output = match output_path {
+        Some(path) => PathBuf::from(path),
+        None => match app
+            .dialog()
+            .file()
+            .add_filter("Project Archive", &[ARCHIVE_EXTENSION])
+            .set_file_name(format!("{}.{}", name, ARCHIVE_EXTENSION))
+            .blocking_save_file()
+        {
+            Some(path) => PathBuf::from(path.to_string()),
+            None => return Err("Export cancelled by user".to_string()),
+        },
+    };
+
+    archive::export_archive(paths.root(), &name, &output, &options.unwrap_or_default())
+}
+
+/// Unpack a project archive into the workspace as a new project
+///
+/// The project keeps its archived name unless `name` is given, with a number
+/// added if that name is taken. Opens a file dialog when `archive_path` is not given.
+#[tauri::command]
+pub async fn import_project_archive(
+    app: AppHandle,
+    archive_path: Option<String>,
+    name: Option<String>,
+) -> Result<ProjectSummary, String> {
+    let archive_path = match archive_path {
+        Some(path) => PathBuf::from(path),
+        None => match app
+            .dialog()
+            .file()
+            .add_filter("Project Archive", &[ARCHIVE_EXTENSION])
+            .blocking_pick_file()
+        {
+            Some(path) => PathBuf::from(path.to_string()),
+            None => return Err("Import cancelled by user".to_string()),
+        },
+    };
+
+    let workspace = ProjectPaths::workspace(&app)?;
+    let (name, path) = archive::import_archive(&archive_path, &workspace, name.as_deref())?;
+    let meta = read_project_meta(&path)?;
+
+    Ok(ProjectSummary::new(name, &path, meta.created_at))
+}
+
 #[tauri::command]
 pub async fn get_project(app: AppHandle, name: String) -> Result<ProjectMeta, String> {
     let paths = ProjectPaths::from_name(&app, &name)?;
@@ -107,6 +385,69 @@ pub async fn get_project(app: AppHandle, name: String) -> Result<ProjectMeta, St
     Ok(meta)
 }
 
+/// Check that the project's files load and agree with each other, without changing anything
+#[tauri::command]
+pub async fn validate_project(
+    app: AppHandle,
+    project_name: String,
+) -> Result<ValidationReport, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    Ok(validation::validate_project(paths.root()))
+}
+
+/// Project files that were found damaged and restored from their backups
+#[tauri::command]
+pub async fn list_recovered_files(
+    app: AppHandle,
+    project_name: String,
+) -> Result<Vec<RecoveryEvent>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    read_recovery_log(paths.root())
+}
+
+/// Reconcile the metadata, `videos/`, scene images and the timeline
+///
+/// With `dry_run` nothing is changed and the report lists what would be.
+/// Videos without a file are only reported, since they may still be
+/// generating; `drop_missing_videos` lists the ids of finished or failed
+/// generations whose records should be removed instead.
+#[tauri::command]
+pub async fn repair_project(
+    app: AppHandle,
+    project_name: String,
+    dry_run: bool,
+    drop_missing_videos: Option<Vec<String>>,
+) -> Result<RepairReport, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let drop_missing_videos = drop_missing_videos.unwrap_or_default();
+    repair::repair_project(paths.root(), dry_run, &drop_missing_videos)
+}
+
+/// Break down the project's disk usage and list files nothing refers to
+#[tauri::command]
+pub async fn project_storage_report(
+    app: AppHandle,
+    project_name: String,
+) -> Result<StorageReport, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    storage::storage_report(paths.root())
+}
+
+/// Delete the orphans listed by `project_storage_report`
+///
+/// With `dry_run` nothing is deleted and the result lists what would be.
+/// `kinds` limits the cleanup to some of "video", "cache" and "temp".
+#[tauri::command]
+pub async fn cleanup_project_storage(
+    app: AppHandle,
+    project_name: String,
+    dry_run: bool,
+    kinds: Option<Vec<String>>,
+) -> Result<CleanupResult, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    storage::clean_up(paths.root(), kinds.as_deref(), dry_run)
+}
+
 #[tauri::command]
 pub async fn add_videos_to_project(
     app: AppHandle,
@@ -114,43 +455,267 @@ pub async fn add_videos_to_project(
     videos_meta: Vec<VideoMeta>,
 ) -> Result<(), String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    let existing_meta = read_project_meta(paths.root())?;
-
-    let mut all_videos = existing_meta.videos;
-    all_videos.extend(videos_meta);
+    update_project_meta(paths.root(), |meta| {
+        meta.videos.extend(videos_meta);
+        meta.path = paths.root().to_string_lossy().to_string();
+        Ok(())
+    })
+}
 
-    let meta = ProjectMeta {
-        videos: all_videos,
-        path: paths.root().to_string_lossy().to_string(),
-        created_at: existing_meta.created_at,
-        storyboard_response_id: existing_meta.storyboard_response_id,
-    };
-    write_project_meta(paths.root(), &meta)?;
-    Ok(())
+/// Clips on the timeline that deleting a generated or imported video would
+/// affect, so the caller can ask what to do with them first
+#[tauri::command]
+pub async fn timeline_clips_using_video(
+    app: AppHandle,
+    project_name: String,
+    video_id: String,
+) -> Result<Vec<TimelineClip>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    repair::timeline_clips_using_video(paths.root(), &video_id)
 }
 
+/// Delete a generated video, returning the ids of timeline clips removed with it
+///
+/// Fails if clips on the timeline use the video, unless `remove_clips` says
+/// whether to remove them (`true`) or keep them without media (`false`).
 #[tauri::command]
 pub async fn delete_video_from_project(
     app: AppHandle,
     project_name: String,
     video_id: String,
+    remove_clips: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let _lock = lock_project(paths.root())?;
+    let video_file_path = paths.video_file(&format!("{}.mp4", video_id));
+    let removed_clips = repair::guard_timeline_usage(paths.root(), &video_file_path, remove_clips)?;
+
+    let video = update_project_meta(paths.root(), |meta| {
+        // Remove video from metadata
+        let video = meta.videos.iter().find(|v| v.id == video_id).cloned();
+        meta.videos.retain(|v| v.id != video_id);
+        meta.selected_takes.retain(|_, selected| *selected != video_id);
+        Ok(video)
+    })?;
+
+    // Move video file to the recycle area
+    if video_file_path.exists() {
+        let record = video.map(RecycledRecord::GeneratedVideo);
+        recycle::recycle_project_file(paths.root(), "video", &video_file_path, record)?;
+    }
+
+    Ok(removed_clips)
+}
+
+#[tauri::command]
+pub async fn set_video_tags(
+    app: AppHandle,
+    project_name: String,
+    video_id: String,
+    tags: Vec<String>,
+) -> Result<VideoMeta, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    update_project_meta(paths.root(), |meta| {
+        let video = meta
+            .videos
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or(format!("Video {} not found", video_id))?;
+        video.tags = normalize_tags(&tags);
+        Ok(video.clone())
+    })
+}
+
+/// Update a video's review fields; fields left as `None` are unchanged.
+///
+/// A `rating` of 0 clears the rating and empty `notes` clear the notes.
+#[tauri::command]
+pub async fn update_video_review(
+    app: AppHandle,
+    project_name: String,
+    video_id: String,
+    rating: Option<u8>,
+    favorite: Option<bool>,
+    notes: Option<String>,
+) -> Result<VideoMeta, String> {
+    if rating.is_some_and(|r| r > 5) {
+        return Err("Rating must be between 0 and 5".to_string());
+    }
+
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    update_project_meta(paths.root(), |meta| {
+        let video = meta
+            .videos
+            .iter_mut()
+            .find(|v| v.id == video_id)
+            .ok_or(format!("Video {} not found", video_id))?;
+
+        if let Some(rating) = rating {
+            video.rating = (rating > 0).then_some(rating);
+        }
+        if let Some(favorite) = favorite {
+            video.favorite = favorite;
+        }
+        if let Some(notes) = notes {
+            let notes = notes.trim();
+            video.notes = (!notes.is_empty()).then(|| notes.to_string());
+        }
+        Ok(video.clone())
+    })
+}
+
+/// Mark `video_id` as the keeper take for a storyboard scene, or clear the selection with `None`
+#[tauri::command]
+pub async fn select_take(
+    app: AppHandle,
+    project_name: String,
+    scene_number: i32,
+    video_id: Option<String>,
 ) -> Result<(), String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    let mut meta = read_project_meta(paths.root())?;
+    update_project_meta(paths.root(), |meta| {
+        match video_id {
+            Some(video_id) => {
+                let video = meta
+                    .videos
+                    .iter()
+                    .find(|v| v.id == video_id)
+                    .ok_or(format!("Video {} not found", video_id))?;
+                if video.scene_number != Some(scene_number) {
+                    return Err(format!(
+                        "Video {} is not a take of scene {}",
+                        video_id, scene_number
+                    ));
+                }
+                meta.selected_takes.insert(scene_number, video_id);
+            }
+            None => {
+                meta.selected_takes.remove(&scene_number);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Search and filter the project's generated and imported videos
+#[tauri::command]
+pub async fn query_library(
+    app: AppHandle,
+    project_name: String,
+    query: Option<LibraryQuery>,
+) -> Result<LibraryPage, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    library::query_library(paths.root(), &query.unwrap_or_default())
+}
 
-    // Remove video from metadata
-    meta.videos.retain(|v| v.id != video_id);
+/// Remix trees of the project's generated videos, or only the tree around `video_id`
+#[tauri::command]
+pub async fn get_remix_lineage(
+    app: AppHandle,
+    project_name: String,
+    video_id: Option<String>,
+) -> Result<RemixLineage, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    build_lineage(&meta.videos, video_id.as_deref())
+}
 
-    write_project_meta(paths.root(), &meta)?;
+/// Report media files with identical content across all projects in the workspace
+///
+/// Only files whose size matches another file are hashed; recorded import
+/// hashes are reused while the file's size and modification time are unchanged.
+#[tauri::command]
+pub async fn find_duplicate_media(app: AppHandle) -> Result<Vec<DuplicateGroup>, String> {
+    let ws = ProjectPaths::workspace(&app)?;
 
-    // Delete video file from videos folder
-    let video_file_path = paths.video_file(&format!("{}.mp4", video_id));
-    if video_file_path.exists() {
-        fs::remove_file(&video_file_path)
-            .map_err(|e| format!("Failed to delete video file: {}", e))?;
+    // (project name, path, import record id, recorded hash)
+    let mut candidates: Vec<(String, PathBuf, Option<String>, Option<String>)> = Vec::new();
+
+    for (name, path) in list_project_directories(&ws)? {
+        let paths = ProjectPaths::new(&path);
+        let meta = read_project_meta(&path).unwrap_or_else(|_| {
+            ProjectMeta::new(path.to_string_lossy().to_string(), current_timestamp())
+        });
+
+        let record_for = |file: &Path| {
+            meta.imported_videos
+                .iter()
+                .find(|r| Path::new(&r.file_path) == file)
+        };
+
+        let mut files: Vec<PathBuf> = Vec::new();
+        let videos_dir = paths.videos_dir();
+        if videos_dir.exists() {
+            for entry in fs::read_dir(&videos_dir)
+                .map_err(|e| format!("Failed to read videos directory: {}", e))?
+            {
+                let entry = entry.map_err(|e| e.to_string())?;
+                if entry.path().is_file() {
+                    files.push(entry.path());
+                }
+            }
+        }
+        for record in meta.imported_videos.iter().filter(|r| r.reference) {
+            let file = PathBuf::from(&record.file_path);
+            if file.exists() && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+
+        for file in files {
+            let record = record_for(&file);
+            let recorded_hash = record.and_then(|r| {
+                let unchanged = file_fingerprint(&file).ok() == Some((r.size, r.modified_at));
+                unchanged.then(|| r.hash.clone())
+            });
+            candidates.push((name.clone(), file, record.map(|r| r.id.clone()), recorded_hash));
+        }
     }
 
-    Ok(())
+    // Group by size first so unique files are never hashed
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (index, (_, file, _, _)) in candidates.iter().enumerate() {
+        if let Ok((size, _)) = file_fingerprint(file) {
+            by_size.entry(size).or_default().push(index);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    for (size, indices) in by_size {
+        if indices.len() < 2 {
+            continue;
+        }
+
+        let mut by_hash: HashMap<String, Vec<DuplicateFile>> = HashMap::new();
+        for index in indices {
+            let (project_name, file, video_id, recorded_hash) = &candidates[index];
+            let hash = match recorded_hash {
+                Some(hash) => hash.clone(),
+                None => hash_file(file)?,
+            };
+            by_hash.entry(hash).or_default().push(DuplicateFile {
+                project_name: project_name.clone(),
+                path: file.to_string_lossy().to_string(),
+                video_id: video_id.clone(),
+            });
+        }
+
+        for (hash, files) in by_hash {
+            if files.len() < 2 {
+                continue;
+            }
+            groups.push(DuplicateGroup {
+                hash,
+                size,
+                redundant_bytes: size * (files.len() as u64 - 1),
+                files,
+            });
+        }
+    }
+
+    // Largest savings first
+    groups.sort_by_key(|g| std::cmp::Reverse(g.redundant_bytes));
+    Ok(groups)
 }
 
 // Scene-based storyboard commands
@@ -168,7 +733,12 @@ pub async fn write_context(
     content: String,
 ) -> Result<(), String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    scenes::write_context(paths.root(), &content)
+    history::record(
+        paths.root(),
+        "Edit context",
+        &[paths.context_file()],
+        || scenes::write_context(paths.root(), &content),
+    )
 }
 
 #[tauri::command]
@@ -200,14 +770,22 @@ pub async fn write_scene(
     duration: String,
     order: Option<i32>,
 ) -> Result<(), String> {
+    sanitize_project_name(&scene_id)?;
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    scenes::write_scene(
+    history::record(
         paths.root(),
-        &scene_id,
-        &title,
-        &description,
-        &duration,
-        order,
+        "Edit scene",
+        &[paths.scene_file(&scene_id), paths.scenes_index()],
+        || {
+            scenes::write_scene(
+                paths.root(),
+                &scene_id,
+                &title,
+                &description,
+                &duration,
+                order,
+            )
+        },
     )
 }
 
@@ -217,8 +795,14 @@ pub async fn delete_scene(
     project_name: String,
     scene_id: String,
 ) -> Result<(), String> {
+    sanitize_project_name(&scene_id)?;
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    scenes::delete_scene(paths.root(), &scene_id)
+    history::record(
+        paths.root(),
+        "Delete scene",
+        &[paths.scene_file(&scene_id), paths.scenes_index()],
+        || scenes::delete_scene(paths.root(), &scene_id),
+    )
 }
 
 #[tauri::command]
@@ -228,7 +812,127 @@ pub async fn reorder_scenes(
     ordered_ids: Vec<String>,
 ) -> Result<(), String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    scenes::reorder_scenes(paths.root(), &ordered_ids)
+    history::record(
+        paths.root(),
+        "Reorder scenes",
+        &[paths.scenes_index()],
+        || scenes::reorder_scenes(paths.root(), &ordered_ids),
+    )
+}
+
+// Undo/redo history for the editor state and storyboard
+
+#[tauri::command]
+pub async fn list_history(app: AppHandle, project_name: String) -> Result<HistoryLog, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    history::load_history(paths.root())
+}
+
+/// Undo the latest edit; returns the entry that was undone, if any
+#[tauri::command]
+pub async fn undo(app: AppHandle, project_name: String) -> Result<Option<HistoryEntry>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    history::undo(paths.root())
+}
+
+/// Redo the last undone edit; returns the entry that was redone, if any
+#[tauri::command]
+pub async fn redo(app: AppHandle, project_name: String) -> Result<Option<HistoryEntry>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    history::redo(paths.root())
+}
+
+/// Return the project to the state right after `entry_id`, or before all
+/// retained history when it is omitted
+#[tauri::command]
+pub async fn restore_history(
+    app: AppHandle,
+    project_name: String,
+    entry_id: Option<String>,
+) -> Result<HistoryLog, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    history::restore_to(paths.root(), entry_id.as_deref())
+}
+
+// Named versions of the editor state and storyboard
+
+#[tauri::command]
+pub async fn save_version(
+    app: AppHandle,
+    project_name: String,
+    name: String,
+    description: Option<String>,
+) -> Result<ProjectVersion, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    versions::save_version(paths.root(), &name, description)
+}
+
+#[tauri::command]
+pub async fn list_versions(
+    app: AppHandle,
+    project_name: String,
+) -> Result<Vec<ProjectVersion>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    versions::list_versions(paths.root())
+}
+
+#[tauri::command]
+pub async fn delete_version(
+    app: AppHandle,
+    project_name: String,
+    version_id: String,
+) -> Result<(), String> {
+    versions::validate_version_id(&version_id)?;
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    versions::delete_version(paths.root(), &version_id)
+}
+
+/// Compare version `from` with version `to`, or with the current project when `to` is omitted
+#[tauri::command]
+pub async fn diff_versions(
+    app: AppHandle,
+    project_name: String,
+    from: String,
+    to: Option<String>,
+) -> Result<VersionDiff, String> {
+    let to = to.unwrap_or_else(|| CURRENT_VERSION.to_string());
+    for version_id in [&from, &to] {
+        if version_id != CURRENT_VERSION {
+            versions::validate_version_id(version_id)?;
+        }
+    }
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    versions::diff_versions(paths.root(), &from, &to)
+}
+
+#[tauri::command]
+pub async fn restore_version(
+    app: AppHandle,
+    project_name: String,
+    version_id: String,
+) -> Result<(), String> {
+    versions::validate_version_id(&version_id)?;
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    versions::restore_version(paths.root(), &version_id)
+}
+
+/// Create a new project from a saved version, sharing the source project's media
+#[tauri::command]
+pub async fn fork_version(
+    app: AppHandle,
+    project_name: String,
+    version_id: String,
+    new_project_name: String,
+) -> Result<ProjectSummary, String> {
+    versions::validate_version_id(&version_id)?;
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let name = sanitize_project_name(&new_project_name)?;
+    let new_path = ProjectPaths::workspace(&app)?.join(&name);
+
+    versions::fork_version(paths.root(), &version_id, &new_path)?;
+    let meta = read_project_meta(&new_path)?;
+
+    Ok(ProjectSummary::new(name, &new_path, meta.created_at))
 }
 
 #[tauri::command]
@@ -1,10 +1,156 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Condvar, LazyLock, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use super::migrations::{load_json, EDITOR_STATE, PROJECT_META};
 use super::paths::ProjectPaths;
-use super::types::ProjectMeta;
+use super::types::{ProjectMeta, RecoveryEvent};
+use crate::commands::video_editor::types::EditorState;
+
+/// Recovery events kept per project; older ones are dropped
+const MAX_RECOVERY_EVENTS: usize = 50;
+/// How long `lock_project` waits for another holder before reporting the project busy
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Pause between attempts to take the lock file held by another process
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+struct HeldLock {
+    owner: ThreadId,
+    depth: usize,
+    /// Advisory lock on the project's lock file, taken by the outermost holder
+    file: Option<fs::File>,
+}
+
+/// Projects locked by this process, keyed by project root
+static PROJECT_LOCKS: LazyLock<(Mutex<HashMap<PathBuf, HeldLock>>, Condvar)> =
+    LazyLock::new(|| (Mutex::new(HashMap::new()), Condvar::new()));
+
+/// Exclusive access to a project's files until dropped.
+///
+/// Locks are re-entrant on the same thread, so helpers that lock can be
+/// called while a caller already holds the lock. Re-entrancy is keyed by
+/// thread, so a locked section must not await: the task could resume on
+/// another thread. The guard is `!Send`, which keeps it out of the futures of
+/// async commands.
+pub struct ProjectLock {
+    root: PathBuf,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ProjectLock {
+    fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+fn project_busy(root: &Path) -> String {
+    format!(
+        "Project {} is busy in another window or task; try again",
+        root.display()
+    )
+}
+
+/// Serialize access to a project within this process and, through an advisory
+/// lock on `.sixseven/project.lock`, with other windows or app instances.
+///
+/// Waits at most `LOCK_TIMEOUT` for another holder, then fails with a
+/// "project busy" error rather than stalling the calling thread.
+pub fn lock_project(project_path: &Path) -> Result<ProjectLock, String> {
+    let root = project_path.to_path_buf();
+    let me = thread::current().id();
+    let (locks, released) = &*PROJECT_LOCKS;
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+
+    {
+        let mut held = locks.lock().map_err(|_| "Project lock registry poisoned")?;
+        loop {
+            match held.get_mut(&root) {
+                Some(lock) if lock.owner == me => {
+                    lock.depth += 1;
+                    return Ok(ProjectLock::new(root));
+                }
+                Some(_) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(project_busy(&root));
+                    }
+                    held = released
+                        .wait_timeout(held, remaining)
+                        .map_err(|_| "Project lock registry poisoned")?
+                        .0;
+                }
+                None => {
+                    held.insert(
+                        root.clone(),
+                        HeldLock {
+                            owner: me,
+                            depth: 1,
+                            file: None,
+                        },
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    // Claimed in-process; now wait for other processes without blocking other projects
+    let guard = ProjectLock::new(root);
+    let paths = ProjectPaths::new(&guard.root);
+    ensure_dir(&paths.metadata_dir())?;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(paths.lock_file())
+        .map_err(|e| format!("Failed to open project lock file: {}", e))?;
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(fs::TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(fs::TryLockError::WouldBlock) => return Err(project_busy(&guard.root)),
+            Err(fs::TryLockError::Error(e)) => {
+                return Err(format!("Failed to lock project: {}", e));
+            }
+        }
+    }
+
+    if let Ok(mut held) = locks.lock() {
+        if let Some(lock) = held.get_mut(&guard.root) {
+            lock.file = Some(file);
+        }
+    }
+    Ok(guard)
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        let (locks, released) = &*PROJECT_LOCKS;
+        let Ok(mut held) = locks.lock() else {
+            return;
+        };
+        if let Some(lock) = held.get_mut(&self.root) {
+            lock.depth -= 1;
+            if lock.depth == 0 {
+                // Dropping the file releases the advisory lock
+                held.remove(&self.root);
+                released.notify_all();
+            }
+        }
+    }
+}
 
 pub fn sanitize_project_name(name: &str) -> Result<String, String> {
     let trimmed = name.trim();
@@ -32,29 +178,262 @@ pub fn current_timestamp() -> i64 {
         .as_secs() as i64
 }
 
+/// Hidden file next to `path`, e.g. `.metadata.json.bak`, so directory scans skip it
+fn hidden_sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}", name, suffix))
+}
+
+/// Last good version of a file written with `write_with_backup`
+pub fn backup_path(path: &Path) -> PathBuf {
+    hidden_sibling(path, "bak")
+}
+
+/// Write `contents` to a temporary file, fsync it and rename it over `path`.
+///
+/// Readers see either the old or the new file, never a partial write.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    let parent = path.parent().ok_or("Invalid file path")?;
+    ensure_dir(parent)?;
+
+    let temp_path = hidden_sibling(path, "tmp");
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+
+    // Persist the rename itself; directories can't be opened for syncing on Windows
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(parent) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Like `write_atomic`, keeping the previous contents of `path` as its backup
+pub fn write_with_backup(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    if path.exists() {
+        let previous =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        write_atomic(&backup_path(path), previous)?;
+    }
+    write_atomic(path, contents)
+}
+
+/// Read and parse a file written with `write_with_backup`, falling back to its backup.
+///
+/// When the backup is used, it is copied back over the damaged file and the
+/// recovery is added to the project's recovery log. `what` names the file in errors.
+pub fn read_recovering<T>(
+    project_path: &Path,
+    path: &Path,
+    what: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<T, String> {
+    let error = match fs::read_to_string(path) {
+        Ok(contents) => match parse(&contents) {
+            Ok(value) => return Ok(value),
+            Err(e) => format!("Failed to parse {}: {}", what, e),
+        },
+        Err(e) => format!("Failed to read {}: {}", what, e),
+    };
+
+    let backup = fs::read_to_string(backup_path(path))
+        .ok()
+        .and_then(|contents| parse(&contents).ok().map(|value| (contents, value)));
+    let Some((contents, value)) = backup else {
+        return Err(error);
+    };
+
+    // Put the good copy back so the next write backs up a valid file
+    write_atomic(path, &contents)?;
+    eprintln!("Recovered {} from backup: {}", path.display(), error);
+    if let Err(e) = record_recovery(project_path, path, &error) {
+        eprintln!("Failed to record recovery: {}", e);
+    }
+
+    Ok(value)
+}
+
+fn record_recovery(project_path: &Path, path: &Path, error: &str) -> Result<(), String> {
+    let mut events = read_recovery_log(project_path)?;
+    events.push(RecoveryEvent {
+        file: path
+            .strip_prefix(project_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string(),
+        error: error.to_string(),
+        recovered_at: current_timestamp(),
+    });
+    if events.len() > MAX_RECOVERY_EVENTS {
+        events.drain(..events.len() - MAX_RECOVERY_EVENTS);
+    }
+
+    let json = serde_json::to_string_pretty(&events)
+        .map_err(|e| format!("Failed to serialize recovery log: {}", e))?;
+    write_atomic(&ProjectPaths::new(project_path).recovery_log_file(), json)
+}
+
+/// Files restored from backup in this project, oldest first
+pub fn read_recovery_log(project_path: &Path) -> Result<Vec<RecoveryEvent>, String> {
+    let path = ProjectPaths::new(project_path).recovery_log_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read recovery log: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse recovery log: {}", e))
+}
+
+/// Size in bytes and modification time (unix seconds) of a file
+pub fn file_fingerprint(path: &Path) -> Result<(u64, i64), String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file metadata for {}: {}", path.display(), e))?;
+    let modified_at = metadata
+        .modified()
+        .map_err(|e| format!("Failed to get file timestamp: {}", e))?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    Ok((metadata.len(), modified_at))
+}
+
+/// Hex-encoded SHA-256 of a file, read in chunks so large media never sits in memory
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open {} for hashing: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {} for hashing: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Recursively collect files under `dir`, skipping hidden entries
+pub fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
 pub fn read_project_meta(project_path: &Path) -> Result<ProjectMeta, String> {
     let paths = ProjectPaths::new(project_path);
     let meta_path = paths.metadata_file();
     if !meta_path.exists() {
-        return Ok(ProjectMeta {
-            videos: Vec::new(),
-            path: project_path.to_string_lossy().to_string(),
-            created_at: current_timestamp(),
-            storyboard_response_id: None,
-        });
+        return Ok(ProjectMeta::new(
+            project_path.to_string_lossy().to_string(),
+            current_timestamp(),
+        ));
     }
-    let contents =
-        fs::read_to_string(&meta_path).map_err(|e| format!("Failed to read metadata: {}", e))?;
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse metadata: {}", e))
+
+    // Paths inside the project are stored relative to it
+    let mut meta: ProjectMeta = load_json(project_path, &meta_path, &PROJECT_META)?;
+    meta.path = project_path.to_string_lossy().to_string();
+    for record in meta.imported_videos.iter_mut().filter(|r| !r.reference) {
+        record.file_path = paths.resolve(&record.file_path);
+    }
+    Ok(meta)
+}
+
+/// Metadata as stored on disk, with paths inside the project made relative
+pub fn project_meta_json(project_path: &Path, meta: &ProjectMeta) -> Result<String, String> {
+    let paths = ProjectPaths::new(project_path);
+    let mut stored = meta.clone();
+    stored.path = String::new();
+    for record in stored.imported_videos.iter_mut().filter(|r| !r.reference) {
+        record.file_path = paths.relativize(&record.file_path);
+    }
+    serde_json::to_string_pretty(&stored)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))
 }
 
 pub fn write_project_meta(project_path: &Path, meta: &ProjectMeta) -> Result<(), String> {
+    let _lock = lock_project(project_path)?;
     let paths = ProjectPaths::new(project_path);
-    let meta_dir = paths.metadata_file();
-    ensure_dir(&meta_dir)?;
-    let json = serde_json::to_string_pretty(meta)
-        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
-    fs::write(&paths.metadata_file(), json).map_err(|e| format!("Failed to write metadata: {}", e))
+    ensure_dir(&paths.metadata_dir())?;
+    let json = project_meta_json(project_path, meta)?;
+    write_with_backup(&paths.metadata_file(), json)
+}
+
+/// The saved editor state with media paths resolved, or `None` if there is none yet
+pub fn read_editor_state(project_path: &Path) -> Result<Option<EditorState>, String> {
+    let paths = ProjectPaths::new(project_path);
+    let editor_state_file = paths.editor_state_file();
+    if !editor_state_file.exists() {
+        return Ok(None);
+    }
+
+    let mut state: EditorState = load_json(project_path, &editor_state_file, &EDITOR_STATE)?;
+    for clip in &mut state.clips {
+        clip.video_path = paths.resolve(&clip.video_path);
+    }
+    state.preview_video_path = state.preview_video_path.map(|p| paths.resolve(&p));
+    Ok(Some(state))
+}
+
+/// Editor state as stored on disk: current schema, media inside the project
+/// relative to it so the project can move
+pub fn editor_state_json(project_path: &Path, state: &EditorState) -> Result<String, String> {
+    let paths = ProjectPaths::new(project_path);
+    let mut stored = state.clone();
+    stored.schema_version = EDITOR_STATE.current();
+    for clip in &mut stored.clips {
+        clip.video_path = paths.relativize(&clip.video_path);
+    }
+    stored.preview_video_path = stored.preview_video_path.map(|p| paths.relativize(&p));
+    serde_json::to_string_pretty(&stored)
+        .map_err(|e| format!("Failed to serialize editor state: {}", e))
+}
+
+/// Read, modify and write the project metadata as one transaction under the project lock.
+///
+/// Nothing is written if `update` fails, and concurrent updates never overwrite each other.
+pub fn update_project_meta<T>(
+    project_path: &Path,
+    update: impl FnOnce(&mut ProjectMeta) -> Result<T, String>,
+) -> Result<T, String> {
+    let _lock = lock_project(project_path)?;
+    let mut meta = read_project_meta(project_path)?;
+    let result = update(&mut meta)?;
+    write_project_meta(project_path, &meta)?;
+    Ok(result)
 }
 
 pub fn list_project_directories(workspace: &Path) -> Result<Vec<(String, PathBuf)>, String> {
@@ -80,3 +459,80 @@ pub fn list_project_directories(workspace: &Path) -> Result<Vec<(String, PathBuf
 
     Ok(items)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TempProject;
+    use super::*;
+    use serde_json::Value;
+
+    fn parse(contents: &str) -> Result<Value, String> {
+        serde_json::from_str(contents).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn writing_with_backup_keeps_the_previous_contents() {
+        let project = TempProject::new();
+        let file = project.metadata_file();
+
+        write_with_backup(&file, r#"{"version": 1}"#).unwrap();
+        assert!(!backup_path(&file).exists());
+        write_with_backup(&file, r#"{"version": 2}"#).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), r#"{"version": 2}"#);
+        let backup = fs::read_to_string(backup_path(&file)).unwrap();
+        assert_eq!(backup, r#"{"version": 1}"#);
+        // The temporary file is renamed into place, not left behind
+        assert!(!hidden_sibling(&file, "tmp").exists());
+    }
+
+    #[test]
+    fn a_corrupt_file_is_recovered_from_its_backup_and_logged() {
+        let project = TempProject::new();
+        let file = project.metadata_file();
+        write_with_backup(&file, r#"{"version": 1}"#).unwrap();
+        write_with_backup(&file, r#"{"version": 2}"#).unwrap();
+        project.write(&file, r#"{"vers"#);
+
+        let value = read_recovering(project.root(), &file, "metadata", parse).unwrap();
+
+        assert_eq!(value["version"], 1);
+        // The good copy is put back in place
+        assert_eq!(fs::read_to_string(&file).unwrap(), r#"{"version": 1}"#);
+        let events = read_recovery_log(project.root()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            Path::new(&events[0].file),
+            file.strip_prefix(project.root()).unwrap()
+        );
+        assert!(events[0].error.starts_with("Failed to parse metadata"));
+    }
+
+    #[test]
+    fn a_missing_file_is_recovered_from_its_backup() {
+        let project = TempProject::new();
+        let file = project.metadata_file();
+        project.write(backup_path(&file), r#"{"version": 1}"#);
+
+        let value = read_recovering(project.root(), &file, "metadata", parse).unwrap();
+
+        assert_eq!(value["version"], 1);
+        assert!(file.exists());
+        let events = read_recovery_log(project.root()).unwrap();
+        assert!(events[0].error.starts_with("Failed to read metadata"));
+    }
+
+    #[test]
+    fn a_corrupt_file_with_a_corrupt_backup_fails_without_logging() {
+        let project = TempProject::new();
+        let file = project.metadata_file();
+        project.write(&file, r#"{"vers"#);
+        project.write(backup_path(&file), "");
+
+        let error = read_recovering(project.root(), &file, "metadata", parse).unwrap_err();
+
+        assert!(error.starts_with("Failed to parse metadata"), "{}", error);
+        assert_eq!(fs::read_to_string(&file).unwrap(), r#"{"vers"#);
+        assert!(read_recovery_log(project.root()).unwrap().is_empty());
+    }
+}
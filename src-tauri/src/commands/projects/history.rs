@@ -0,0 +1,312 @@
+//! Undo/redo history for the editor state and storyboard
+//!
+//! Each recorded edit stores the content of the files it touched before and
+//! after the change. File contents are kept once per distinct version in a
+//! content-addressed store under `.sixseven/history/blobs/`, so repeated saves
+//! of the same state cost nothing. `cursor` is the number of entries currently
+//! applied; entries past it can be redone until a new edit is recorded.
+
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::filesystem::{
+    backup_path, current_timestamp, lock_project, read_recovering, write_atomic, write_with_backup,
+};
+use super::paths::ProjectPaths;
+use super::types::{HistoryChange, HistoryEntry, HistoryLog};
+
+/// Oldest entries beyond this are dropped along with content no longer referenced
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read(path)
+        .map(Some)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Path relative to the project root, with `/` separators so logs are portable
+pub(crate) fn relative_path(project_path: &Path, path: &Path) -> String {
+    path.strip_prefix(project_path)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn load_history(project_path: &Path) -> Result<HistoryLog, String> {
+    let path = ProjectPaths::new(project_path).history_file();
+    if !path.exists() {
+        return Ok(HistoryLog::default());
+    }
+    read_recovering(project_path, &path, "history", |contents| {
+        serde_json::from_str(contents).map_err(|e| e.to_string())
+    })
+}
+
+fn save_history(project_path: &Path, log: &HistoryLog) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(log)
+        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    write_with_backup(&ProjectPaths::new(project_path).history_file(), json)
+}
+
+/// Store `content` in the blob store and return its hash
+fn store_blob(paths: &ProjectPaths, content: &[u8]) -> Result<String, String> {
+    let hash = format!("{:x}", Sha256::digest(content));
+    let blob = paths.history_blob(&hash);
+    if !blob.exists() {
+        write_atomic(&blob, content)?;
+    }
+    Ok(hash)
+}
+
+/// Delete blobs that no entry refers to any more
+fn collect_garbage(paths: &ProjectPaths, log: &HistoryLog) -> Result<(), String> {
+    let blobs_dir = paths.history_blobs_dir();
+    if !blobs_dir.exists() {
+        return Ok(());
+    }
+
+    let referenced: HashSet<&str> = log
+        .entries
+        .iter()
+        .flat_map(|entry| entry.changes.iter())
+        .flat_map(|change| [change.before.as_deref(), change.after.as_deref()])
+        .flatten()
+        .collect();
+
+    for entry in
+        fs::read_dir(&blobs_dir).map_err(|e| format!("Failed to read history blobs: {}", e))?
+    {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !referenced.contains(name.as_str()) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Run `op`, recording the changes it makes to `files` as one history entry.
+///
+/// Nothing is recorded if `op` fails or leaves the files unchanged. Failing to
+/// record never fails the edit itself. The project stays locked throughout so
+/// the entry only captures this edit.
+pub fn record<T>(
+    project_path: &Path,
+    label: &str,
+    files: &[PathBuf],
+    op: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let _lock = lock_project(project_path)?;
+    let before = files
+        .iter()
+        .map(|file| read_optional(file))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let result = op()?;
+
+    if let Err(e) = append_entry(project_path, label, files, before) {
+        eprintln!("Failed to record history for '{}': {}", label, e);
+    }
+
+    Ok(result)
+}
+
+fn append_entry(
+    project_path: &Path,
+    label: &str,
+    files: &[PathBuf],
+    before: Vec<Option<Vec<u8>>>,
+) -> Result<(), String> {
+    let paths = ProjectPaths::new(project_path);
+
+    let mut changes = Vec::new();
+    for (file, before) in files.iter().zip(before) {
+        let after = read_optional(file)?;
+        if after == before {
+            continue;
+        }
+        changes.push(HistoryChange {
+            path: relative_path(project_path, file),
+            before: before.map(|c| store_blob(&paths, &c)).transpose()?,
+            after: after.map(|c| store_blob(&paths, &c)).transpose()?,
+        });
+    }
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut log = load_history(project_path)?;
+
+    // A new edit discards anything that was undone
+    log.entries.truncate(log.cursor);
+    log.entries.push(HistoryEntry {
+        id: Uuid::new_v4().to_string(),
+        label: label.to_string(),
+        created_at: current_timestamp(),
+        changes,
+    });
+    if log.entries.len() > MAX_HISTORY_ENTRIES {
+        let excess = log.entries.len() - MAX_HISTORY_ENTRIES;
+        log.entries.drain(..excess);
+    }
+    log.cursor = log.entries.len();
+
+    save_history(project_path, &log)?;
+    collect_garbage(&paths, &log)
+}
+
+/// Rewrite every recorded content of `file`, e.g. after the project moved.
+///
+/// Content that `rewrite` fails on is kept as it was.
+pub fn rewrite_file(
+    project_path: &Path,
+    file: &Path,
+    rewrite: impl Fn(&[u8]) -> Result<Vec<u8>, String>,
+) -> Result<(), String> {
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+    let key = relative_path(project_path, file);
+    let mut log = load_history(project_path)?;
+
+    // Old hash to new, so content shared between entries is rewritten once
+    let mut rewritten: HashMap<String, String> = HashMap::new();
+    let changes = log
+        .entries
+        .iter_mut()
+        .flat_map(|entry| entry.changes.iter_mut())
+        .filter(|change| change.path == key);
+    for change in changes {
+        for hash in [&mut change.before, &mut change.after]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(new_hash) = rewritten.get(hash.as_str()) {
+                *hash = new_hash.clone();
+                continue;
+            }
+
+            let data = fs::read(paths.history_blob(hash))
+                .map_err(|e| format!("Failed to read history blob: {}", e))?;
+            let new_hash = match rewrite(&data) {
+                Ok(content) => store_blob(&paths, &content)?,
+                Err(e) => {
+                    eprintln!("Failed to rewrite history of {}: {}", key, e);
+                    hash.clone()
+                }
+            };
+            rewritten.insert(hash.clone(), new_hash.clone());
+            *hash = new_hash;
+        }
+    }
+
+    if rewritten.is_empty() {
+        return Ok(());
+    }
+    save_history(project_path, &log)?;
+    collect_garbage(&paths, &log)
+}
+
+/// Write each changed file back to its `before` (undo) or `after` (redo) content
+fn apply_entry(project_path: &Path, entry: &HistoryEntry, undo: bool) -> Result<(), String> {
+    let paths = ProjectPaths::new(project_path);
+
+    for change in &entry.changes {
+        let target = change
+            .path
+            .split('/')
+            .fold(project_path.to_path_buf(), |path, part| path.join(part));
+        let content = if undo { &change.before } else { &change.after };
+
+        match content {
+            Some(hash) => {
+                let data = fs::read(paths.history_blob(hash))
+                    .map_err(|e| format!("Failed to read history blob: {}", e))?;
+                write_with_backup(&target, data)?;
+            }
+            None => {
+                if target.exists() {
+                    fs::remove_file(&target)
+                        .map_err(|e| format!("Failed to remove {}: {}", change.path, e))?;
+                }
+                let _ = fs::remove_file(backup_path(&target));
+                // Drop the folder left behind when a created scene is undone; the
+                // storyboard and scenes folders themselves always stay
+                if let Some(scene_dir) = target
+                    .parent()
+                    .filter(|dir| dir.parent() == Some(paths.scenes_dir().as_path()))
+                {
+                    let _ = fs::remove_dir(scene_dir);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Undo the most recent applied entry, returning it, or `None` if there is nothing to undo
+pub fn undo(project_path: &Path) -> Result<Option<HistoryEntry>, String> {
+    let _lock = lock_project(project_path)?;
+    let mut log = load_history(project_path)?;
+    if log.cursor == 0 {
+        return Ok(None);
+    }
+
+    let entry = log.entries[log.cursor - 1].clone();
+    apply_entry(project_path, &entry, true)?;
+    log.cursor -= 1;
+    save_history(project_path, &log)?;
+    Ok(Some(entry))
+}
+
+/// Redo the next undone entry, returning it, or `None` if there is nothing to redo
+pub fn redo(project_path: &Path) -> Result<Option<HistoryEntry>, String> {
+    let _lock = lock_project(project_path)?;
+    let mut log = load_history(project_path)?;
+    if log.cursor >= log.entries.len() {
+        return Ok(None);
+    }
+
+    let entry = log.entries[log.cursor].clone();
+    apply_entry(project_path, &entry, false)?;
+    log.cursor += 1;
+    save_history(project_path, &log)?;
+    Ok(Some(entry))
+}
+
+/// Undo or redo until the project is in the state right after `entry_id`,
+/// or before every retained entry when `entry_id` is `None`
+pub fn restore_to(project_path: &Path, entry_id: Option<&str>) -> Result<HistoryLog, String> {
+    let _lock = lock_project(project_path)?;
+    let log = load_history(project_path)?;
+    let target = match entry_id {
+        Some(id) => {
+            log.entries
+                .iter()
+                .position(|e| e.id == id)
+                .ok_or(format!("History entry {} not found", id))?
+                + 1
+        }
+        None => 0,
+    };
+
+    let mut cursor = log.cursor;
+    while cursor > target {
+        undo(project_path)?;
+        cursor -= 1;
+    }
+    while cursor < target {
+        redo(project_path)?;
+        cursor += 1;
+    }
+
+    load_history(project_path)
+}
@@ -0,0 +1,356 @@
+//! Media library queries over generated and imported videos
+//!
+//! Library entries are flattened from `ProjectMeta` into a small index cached
+//! at `.sixseven/cache/library_index.json`. The index is rebuilt whenever the
+//! content hash of `metadata.json` changes.
+
+use std::fs;
+use std::path::Path;
+
+use super::filesystem::{ensure_dir, hash_file, read_project_meta, write_atomic};
+use super::paths::ProjectPaths;
+use super::types::{
+    ImportedVideoMeta, LibraryIndex, LibraryItem, LibraryPage, LibraryQuery, VideoMeta,
+};
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Trim, drop empty and de-duplicate user tags, keeping their order
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !cleaned.iter().any(|c| c.eq_ignore_ascii_case(tag)) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+fn search_text(parts: &[Option<&str>], tags: &[String]) -> String {
+    let mut text: Vec<String> = parts.iter().flatten().map(|p| p.to_lowercase()).collect();
+    text.extend(tags.iter().map(|t| t.to_lowercase()));
+    text.join("\n")
+}
+
+fn generated_item(paths: &ProjectPaths, video: &VideoMeta) -> LibraryItem {
+    let title = match (video.scene_number, &video.scene_title) {
+        (Some(number), Some(title)) => format!("Scene {}: {}", number, title),
+        _ => format!("Video {}", &video.id[..video.id.len().min(8)]),
+    };
+
+    LibraryItem {
+        id: video.id.clone(),
+        kind: "generated".to_string(),
+        search_text: search_text(
+            &[
+                Some(&title),
+                Some(&video.prompt),
+                video.remix_prompt.as_deref(),
+                video.scene_title.as_deref(),
+                Some(&video.model),
+            ],
+            &video.tags,
+        ),
+        title,
+        prompt: Some(video.prompt.clone()),
+        model: Some(video.model.clone()),
+        resolution: Some(video.resolution.clone()),
+        duration: video.duration as f64,
+        scene_number: video.scene_number,
+        scene_title: video.scene_title.clone(),
+        created_at: video.created_at,
+        tags: video.tags.clone(),
+        video_path: paths
+            .video_file(&format!("{}.mp4", video.id))
+            .to_string_lossy()
+            .to_string(),
+    }
+}
+
+fn imported_item(record: &ImportedVideoMeta) -> LibraryItem {
+    let title = Path::new(&record.original_name)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.is_empty())
+        .unwrap_or(&record.id)
+        .to_string();
+    let resolution = (record.probe.width > 0 && record.probe.height > 0)
+        .then(|| format!("{}x{}", record.probe.width, record.probe.height));
+
+    LibraryItem {
+        id: record.id.clone(),
+        kind: "imported".to_string(),
+        search_text: search_text(&[Some(&title), Some(&record.original_name)], &record.tags),
+        title,
+        prompt: None,
+        model: None,
+        resolution,
+        duration: record.probe.duration,
+        scene_number: None,
+        scene_title: None,
+        created_at: record.imported_at,
+        tags: record.tags.clone(),
+        video_path: record.file_path.clone(),
+    }
+}
+
+/// Load the library index, rebuilding it from project metadata when stale
+pub fn load_library_index(project_path: &Path) -> Result<LibraryIndex, String> {
+    let paths = ProjectPaths::new(project_path);
+    let meta_file = paths.metadata_file();
+    let source_hash = if meta_file.exists() {
+        hash_file(&meta_file)?
+    } else {
+        String::new()
+    };
+
+    let index_file = paths.library_index_file();
+    if index_file.exists() {
+        let cached = fs::read_to_string(&index_file)
+            .ok()
+            .and_then(|json| serde_json::from_str::<LibraryIndex>(&json).ok());
+        if let Some(index) = cached {
+            if index.source_hash == source_hash {
+                return Ok(index);
+            }
+        }
+    }
+
+    let meta = read_project_meta(project_path)?;
+    let mut items: Vec<LibraryItem> = meta
+        .videos
+        .iter()
+        .map(|video| generated_item(&paths, video))
+        .collect();
+    items.extend(meta.imported_videos.iter().map(imported_item));
+
+    let index = LibraryIndex { source_hash, items };
+
+    // The index is only a cache; failing to store it must not fail the query
+    if ensure_dir(&paths.cache_dir()).is_ok() {
+        if let Ok(json) = serde_json::to_string(&index) {
+            let _ = write_atomic(&index_file, json);
+        }
+    }
+
+    Ok(index)
+}
+
+fn matches(item: &LibraryItem, query: &LibraryQuery, terms: &[String]) -> bool {
+    if !terms
+        .iter()
+        .all(|term| item.search_text.contains(term.as_str()))
+    {
+        return false;
+    }
+    if let Some(kinds) = &query.kinds {
+        if !kinds.contains(&item.kind) {
+            return false;
+        }
+    }
+    if let Some(models) = &query.models {
+        if !item.model.as_ref().is_some_and(|m| models.contains(m)) {
+            return false;
+        }
+    }
+    if let Some(resolutions) = &query.resolutions {
+        if !item
+            .resolution
+            .as_ref()
+            .is_some_and(|r| resolutions.contains(r))
+        {
+            return false;
+        }
+    }
+    if let Some(scenes) = &query.scene_numbers {
+        if !item.scene_number.is_some_and(|n| scenes.contains(&n)) {
+            return false;
+        }
+    }
+    if query.min_duration.is_some_and(|min| item.duration < min)
+        || query.max_duration.is_some_and(|max| item.duration > max)
+        || query
+            .created_after
+            .is_some_and(|after| item.created_at < after)
+        || query
+            .created_before
+            .is_some_and(|before| item.created_at > before)
+    {
+        return false;
+    }
+    if let Some(tags) = &query.tags {
+        let has_all = tags
+            .iter()
+            .all(|tag| item.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+        if !has_all {
+            return false;
+        }
+    }
+    true
+}
+
+/// Filter, sort and paginate the project's library
+pub fn query_library(project_path: &Path, query: &LibraryQuery) -> Result<LibraryPage, String> {
+    let index = load_library_index(project_path)?;
+
+    let terms: Vec<String> = query
+        .text
+        .as_deref()
+        .unwrap_or("")
+        .split_whitespace()
+        .map(|t| t.to_lowercase())
+        .collect();
+
+    let mut items: Vec<LibraryItem> = index
+        .items
+        .into_iter()
+        .filter(|item| matches(item, query, &terms))
+        .collect();
+
+    match query.sort_by.as_deref() {
+        Some("title") => items.sort_by_key(|item| item.title.to_lowercase()),
+        Some("duration") => items.sort_by(|a, b| a.duration.total_cmp(&b.duration)),
+        Some("scene") => {
+            items.sort_by_key(|item| (item.scene_number.unwrap_or(i32::MAX), item.created_at))
+        }
+        _ => items.sort_by_key(|item| item.created_at),
+    }
+    // Newest first unless ascending order was requested
+    let descending = query
+        .descending
+        .unwrap_or(matches!(query.sort_by.as_deref(), None | Some("createdAt")));
+    if descending {
+        items.reverse();
+    }
+
+    let total = items.len();
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let items = items.into_iter().skip(offset).take(limit).collect();
+
+    Ok(LibraryPage {
+        items,
+        total,
+        offset,
+        limit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn item() -> LibraryItem {
+        let tags = strings(&["Hero", "night"]);
+        LibraryItem {
+            id: "v1".to_string(),
+            kind: "generated".to_string(),
+            title: "Rooftop Chase".to_string(),
+            prompt: Some("A chase across wet rooftops".to_string()),
+            model: Some("sora-2".to_string()),
+            resolution: Some("1280x720".to_string()),
+            duration: 8.0,
+            scene_number: Some(2),
+            scene_title: Some("Chase".to_string()),
+            created_at: 1_000,
+            search_text: search_text(
+                &[Some("Rooftop Chase"), Some("A chase across wet rooftops")],
+                &tags,
+            ),
+            tags,
+            video_path: "videos/v1.mp4".to_string(),
+        }
+    }
+
+    fn matches_query(query: LibraryQuery) -> bool {
+        let terms: Vec<String> = query
+            .text
+            .as_deref()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .collect();
+        matches(&item(), &query, &terms)
+    }
+
+    #[test]
+    fn normalize_tags_trims_and_drops_empty_tags() {
+        let tags = strings(&["  hero ", "", "   ", "night"]);
+        assert_eq!(normalize_tags(&tags), ["hero", "night"]);
+    }
+
+    #[test]
+    fn normalize_tags_keeps_the_first_spelling_of_a_repeated_tag() {
+        let tags = strings(&["Hero", "night", "HERO", "hero "]);
+        assert_eq!(normalize_tags(&tags), ["Hero", "night"]);
+    }
+
+    #[test]
+    fn an_empty_query_matches_everything() {
+        assert!(matches_query(LibraryQuery::default()));
+    }
+
+    #[test]
+    fn every_search_term_must_appear() {
+        assert!(matches_query(LibraryQuery {
+            text: Some("WET chase".to_string()),
+            ..Default::default()
+        }));
+        assert!(!matches_query(LibraryQuery {
+            text: Some("wet desert".to_string()),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn list_filters_match_any_listed_value() {
+        assert!(matches_query(LibraryQuery {
+            models: Some(strings(&["sora-2-pro", "sora-2"])),
+            scene_numbers: Some(vec![1, 2]),
+            ..Default::default()
+        }));
+        assert!(!matches_query(LibraryQuery {
+            kinds: Some(strings(&["imported"])),
+            ..Default::default()
+        }));
+        assert!(!matches_query(LibraryQuery {
+            resolutions: Some(strings(&["720x1280"])),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn duration_and_date_bounds_are_inclusive() {
+        assert!(matches_query(LibraryQuery {
+            min_duration: Some(8.0),
+            max_duration: Some(8.0),
+            created_after: Some(1_000),
+            created_before: Some(1_000),
+            ..Default::default()
+        }));
+        assert!(!matches_query(LibraryQuery {
+            max_duration: Some(4.0),
+            ..Default::default()
+        }));
+        assert!(!matches_query(LibraryQuery {
+            created_after: Some(1_001),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn tag_filters_need_every_tag_ignoring_case() {
+        assert!(matches_query(LibraryQuery {
+            tags: Some(strings(&["hero", "NIGHT"])),
+            ..Default::default()
+        }));
+        assert!(!matches_query(LibraryQuery {
+            tags: Some(strings(&["hero", "day"])),
+            ..Default::default()
+        }));
+    }
+}
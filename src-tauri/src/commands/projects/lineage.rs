@@ -0,0 +1,406 @@
+//! Remix lineage of generated videos
+//!
+//! Each remix records the video it was made from in `remixed_from_video_id`.
+//! Following those links gives a forest of remix trees; a remix whose parent
+//! is no longer in the project is an orphan and is treated as a root. Links
+//! that form a cycle, which only damaged metadata can hold, are cut at the
+//! oldest video of the cycle, which then becomes a root as well.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::types::{OrphanedRemix, PromptDiffSegment, RemixLineage, RemixNode, VideoMeta};
+
+/// The prompt a video was actually generated from
+fn effective_prompt(video: &VideoMeta) -> &str {
+    video.remix_prompt.as_deref().unwrap_or(&video.prompt)
+}
+
+/// Word-level diff of two prompts, with consecutive words of the same kind merged
+pub fn diff_prompts(old: &str, new: &str) -> Vec<PromptDiffSegment> {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    let (n, m) = (old_words.len(), new_words.len());
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_words[i] == new_words[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut segments: Vec<PromptDiffSegment> = Vec::new();
+    let mut push = |kind: &str, word: &str| match segments.last_mut() {
+        Some(last) if last.kind == kind => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => segments.push(PromptDiffSegment {
+            kind: kind.to_string(),
+            text: word.to_string(),
+        }),
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_words[i] == new_words[j] {
+            push("same", old_words[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push("removed", old_words[i]);
+            i += 1;
+        } else {
+            push("added", new_words[j]);
+            j += 1;
+        }
+    }
+    for word in &old_words[i..] {
+        push("removed", word);
+    }
+    for word in &new_words[j..] {
+        push("added", word);
+    }
+
+    segments
+}
+
+/// The oldest video of the remix cycle that the parent links of `start` run
+/// into. Only called for videos not reachable from any root, whose parents
+/// are all in the project.
+fn cycle_root<'a>(start: &'a VideoMeta, by_id: &HashMap<&str, &'a VideoMeta>) -> &'a VideoMeta {
+    let mut chain: Vec<&VideoMeta> = Vec::new();
+    let mut current = start;
+    loop {
+        if let Some(first) = chain.iter().position(|v| v.id == current.id) {
+            return chain[first..]
+                .iter()
+                .min_by_key(|v| v.created_at)
+                .copied()
+                .unwrap_or(current);
+        }
+        chain.push(current);
+        match current
+            .remixed_from_video_id
+            .as_deref()
+            .and_then(|id| by_id.get(id))
+        {
+            Some(parent) => current = parent,
+            None => return current,
+        }
+    }
+}
+
+/// Build the remix forest for `videos`, or only the tree around `focus_id` when given.
+///
+/// With a focus video, `ancestors` runs from the root down to its parent and
+/// `descendants` lists every remix made from it, breadth first.
+pub fn build_lineage(videos: &[VideoMeta], focus_id: Option<&str>) -> Result<RemixLineage, String> {
+    let by_id: HashMap<&str, &VideoMeta> = videos.iter().map(|v| (v.id.as_str(), v)).collect();
+
+    let mut orphans = Vec::new();
+    let mut children: HashMap<&str, Vec<&VideoMeta>> = HashMap::new();
+    let mut roots: Vec<&VideoMeta> = Vec::new();
+
+    for video in videos {
+        match video.remixed_from_video_id.as_deref() {
+            Some(parent_id) if by_id.contains_key(parent_id) => {
+                children.entry(parent_id).or_default().push(video);
+            }
+            Some(parent_id) => {
+                orphans.push(OrphanedRemix {
+                    video_id: video.id.clone(),
+                    missing_parent_id: parent_id.to_string(),
+                });
+                roots.push(video);
+            }
+            None => roots.push(video),
+        }
+    }
+
+    for list in children.values_mut() {
+        list.sort_by_key(|v| v.created_at);
+    }
+    roots.sort_by_key(|v| v.created_at);
+
+    // Walk every tree from its root so depths and diffs follow the parent links.
+    // Videos left unreached are in a cycle, or descend from one.
+    let mut nodes: Vec<RemixNode> = Vec::new();
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut cut: HashSet<&str> = HashSet::new();
+    let mut queue: VecDeque<(&VideoMeta, usize)> = roots.iter().map(|v| (*v, 0)).collect();
+
+    loop {
+        while let Some((video, depth)) = queue.pop_front() {
+            if !visited.insert(video.id.as_str()) {
+                continue;
+            }
+
+            let parent = video
+                .remixed_from_video_id
+                .as_deref()
+                .filter(|_| !cut.contains(video.id.as_str()))
+                .and_then(|id| by_id.get(id));
+            let video_children: Vec<&VideoMeta> = children
+                .get(video.id.as_str())
+                .into_iter()
+                .flatten()
+                .filter(|c| !cut.contains(c.id.as_str()))
+                .copied()
+                .collect();
+
+            nodes.push(RemixNode {
+                video_id: video.id.clone(),
+                parent_id: parent.map(|p| p.id.clone()),
+                children: video_children.iter().map(|c| c.id.clone()).collect(),
+                depth,
+                prompt: effective_prompt(video).to_string(),
+                prompt_diff: parent
+                    .map(|p| diff_prompts(effective_prompt(p), effective_prompt(video)))
+                    .unwrap_or_default(),
+                created_at: video.created_at,
+                scene_number: video.scene_number,
+                sample_number: video.sample_number,
+            });
+
+            queue.extend(video_children.into_iter().map(|c| (c, depth + 1)));
+        }
+
+        let Some(unreached) = videos.iter().find(|v| !visited.contains(v.id.as_str())) else {
+            break;
+        };
+        let root = cycle_root(unreached, &by_id);
+        cut.insert(root.id.as_str());
+        roots.push(root);
+        queue.push_back((root, 0));
+    }
+
+    let root_ids: Vec<String> = roots.iter().map(|v| v.id.clone()).collect();
+
+    let Some(focus_id) = focus_id else {
+        return Ok(RemixLineage {
+            roots: root_ids,
+            nodes,
+            ancestors: Vec::new(),
+            descendants: Vec::new(),
+            orphans,
+        });
+    };
+
+    if !by_id.contains_key(focus_id) {
+        return Err(format!("Video {} not found", focus_id));
+    }
+
+    let node_index: HashMap<&str, &RemixNode> =
+        nodes.iter().map(|n| (n.video_id.as_str(), n)).collect();
+
+    let mut ancestors: Vec<String> = Vec::new();
+    let mut current = node_index.get(focus_id).and_then(|n| n.parent_id.clone());
+    while let Some(id) = current {
+        if ancestors.contains(&id) {
+            break;
+        }
+        current = node_index
+            .get(id.as_str())
+            .and_then(|n| n.parent_id.clone());
+        ancestors.push(id);
+    }
+    ancestors.reverse();
+
+    let mut descendants: Vec<String> = Vec::new();
+    let mut pending: VecDeque<&str> = VecDeque::from([focus_id]);
+    while let Some(id) = pending.pop_front() {
+        if let Some(node) = node_index.get(id) {
+            for child in &node.children {
+                descendants.push(child.clone());
+                pending.push_back(child);
+            }
+        }
+    }
+
+    let keep: HashSet<&str> = ancestors
+        .iter()
+        .chain(descendants.iter())
+        .map(String::as_str)
+        .chain([focus_id])
+        .collect();
+    let root_id = ancestors.first().map(String::as_str).unwrap_or(focus_id);
+
+    let orphans = orphans
+        .into_iter()
+        .filter(|o| keep.contains(o.video_id.as_str()))
+        .collect();
+    let nodes = nodes
+        .into_iter()
+        .filter(|n| keep.contains(n.video_id.as_str()))
+        .collect();
+
+    Ok(RemixLineage {
+        roots: vec![root_id.to_string()],
+        nodes,
+        ancestors,
+        descendants,
+        orphans,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(id: &str, created_at: i64, parent: Option<&str>, prompt: &str) -> VideoMeta {
+        VideoMeta {
+            id: id.to_string(),
+            prompt: "a red car at night".to_string(),
+            model: "sora-2".to_string(),
+            resolution: "1280x720".to_string(),
+            duration: 4,
+            created_at,
+            scene_number: Some(1),
+            scene_title: None,
+            sample_number: None,
+            remixed_from_video_id: parent.map(str::to_string),
+            remix_prompt: parent.map(|_| prompt.to_string()),
+            tags: Vec::new(),
+            rating: None,
+            favorite: false,
+            notes: None,
+        }
+    }
+
+    /// `a` has remixes `b` and `c`, `d` remixes `b`, `e` remixes a deleted video
+    /// and `f` stands alone
+    fn videos() -> Vec<VideoMeta> {
+        vec![
+            video("d", 4, Some("b"), "a blue car at dawn"),
+            video("c", 3, Some("a"), "a red truck at night"),
+            video("b", 2, Some("a"), "a blue car at night"),
+            video("a", 1, None, ""),
+            video("e", 5, Some("gone"), "a green car"),
+            video("f", 0, None, ""),
+        ]
+    }
+
+    fn ids(nodes: &[RemixNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.video_id.as_str()).collect()
+    }
+
+    fn segments(diff: &[PromptDiffSegment]) -> Vec<(&str, &str)> {
+        diff.iter()
+            .map(|s| (s.kind.as_str(), s.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_prompts_merges_runs_of_words() {
+        let diff = diff_prompts("a red car at night", "a blue car at night, fast");
+        assert_eq!(
+            segments(&diff),
+            [
+                ("same", "a"),
+                ("removed", "red"),
+                ("added", "blue"),
+                ("same", "car at"),
+                ("removed", "night"),
+                ("added", "night, fast"),
+            ]
+        );
+    }
+
+    #[test]
+    fn whole_project_lineage_walks_each_tree_breadth_first() {
+        let lineage = build_lineage(&videos(), None).unwrap();
+
+        assert_eq!(lineage.roots, ["f", "a", "e"]);
+        assert_eq!(ids(&lineage.nodes), ["f", "a", "e", "b", "c", "d"]);
+        let depths: Vec<usize> = lineage.nodes.iter().map(|n| n.depth).collect();
+        assert_eq!(depths, [0, 0, 0, 1, 1, 2]);
+        assert!(lineage.ancestors.is_empty() && lineage.descendants.is_empty());
+
+        let a = &lineage.nodes[1];
+        assert_eq!(a.children, ["b", "c"]);
+        assert!(a.prompt_diff.is_empty());
+    }
+
+    #[test]
+    fn remixes_of_missing_videos_are_orphaned_roots() {
+        let lineage = build_lineage(&videos(), None).unwrap();
+
+        assert_eq!(lineage.orphans.len(), 1);
+        assert_eq!(lineage.orphans[0].video_id, "e");
+        assert_eq!(lineage.orphans[0].missing_parent_id, "gone");
+        let e = lineage.nodes.iter().find(|n| n.video_id == "e").unwrap();
+        assert_eq!(e.parent_id, None);
+        assert_eq!(e.depth, 0);
+    }
+
+    #[test]
+    fn remix_nodes_diff_against_their_parents_prompt() {
+        let lineage = build_lineage(&videos(), None).unwrap();
+        let d = lineage.nodes.iter().find(|n| n.video_id == "d").unwrap();
+
+        assert_eq!(d.prompt, "a blue car at dawn");
+        assert_eq!(
+            segments(&d.prompt_diff),
+            [
+                ("same", "a blue car at"),
+                ("removed", "night"),
+                ("added", "dawn")
+            ]
+        );
+    }
+
+    #[test]
+    fn focused_lineage_keeps_only_the_focus_ancestors_and_descendants() {
+        let lineage = build_lineage(&videos(), Some("b")).unwrap();
+
+        assert_eq!(lineage.roots, ["a"]);
+        assert_eq!(lineage.ancestors, ["a"]);
+        assert_eq!(lineage.descendants, ["d"]);
+        assert_eq!(ids(&lineage.nodes), ["a", "b", "d"]);
+        assert!(lineage.orphans.is_empty());
+    }
+
+    #[test]
+    fn focusing_an_orphan_keeps_its_orphan_record() {
+        let lineage = build_lineage(&videos(), Some("e")).unwrap();
+
+        assert_eq!(lineage.roots, ["e"]);
+        assert!(lineage.ancestors.is_empty());
+        assert_eq!(lineage.orphans.len(), 1);
+    }
+
+    #[test]
+    fn focusing_an_unknown_video_fails() {
+        assert!(build_lineage(&videos(), Some("missing")).is_err());
+    }
+
+    #[test]
+    fn remix_cycles_are_cut_at_their_oldest_video() {
+        // `x` and `y` remix each other and `z` remixes `y`
+        let videos = vec![
+            video("z", 0, Some("y"), "z"),
+            video("y", 2, Some("x"), "y"),
+            video("x", 1, Some("y"), "x"),
+        ];
+
+        let lineage = build_lineage(&videos, None).unwrap();
+        assert_eq!(lineage.roots, ["x"]);
+        assert_eq!(ids(&lineage.nodes), ["x", "y", "z"]);
+        let x = &lineage.nodes[0];
+        assert_eq!(x.parent_id, None);
+        assert_eq!(x.children, ["y"]);
+        let y = &lineage.nodes[1];
+        assert_eq!(y.parent_id.as_deref(), Some("x"));
+        assert_eq!(y.children, ["z"]);
+
+        let lineage = build_lineage(&videos, Some("y")).unwrap();
+        assert_eq!(lineage.roots, ["x"]);
+        assert_eq!(lineage.ancestors, ["x"]);
+        assert_eq!(lineage.descendants, ["z"]);
+    }
+}
@@ -0,0 +1,501 @@
+//! Schema versions and migrations for persisted project files
+//!
+//! Every JSON file records the schema it was written with. Each kind of file
+//! has a list of migrations where `migrations[n]` upgrades version `n` to
+//! `n + 1`; files written before versions existed are version 0. Files are
+//! upgraded when they are loaded, after the original is copied to
+//! `.sixseven/migrations/v<version>/`. Scene markdown carries its version in a
+//! `<!-- schema: N -->` comment on its first line.
+//!
+//! Migrations get the project's current root, since early versions stored
+//! absolute paths that have to be made relative to it.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::filesystem::{ensure_dir, lock_project, read_recovering, write_with_backup};
+use super::paths::ProjectPaths;
+
+type Migration = fn(&mut Value, &Path) -> Result<(), String>;
+
+/// A kind of versioned JSON file
+pub struct Schema {
+    pub what: &'static str,
+    /// Top-level key holding the version, following the file's field naming
+    key: &'static str,
+    migrations: &'static [Migration],
+}
+
+impl Schema {
+    /// Version written by this build
+    pub const fn current(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    fn version_of(&self, value: &Value) -> u32 {
+        value
+            .get(self.key)
+            .and_then(Value::as_u64)
+            .map(|v| v as u32)
+            .unwrap_or(0)
+    }
+
+    /// Version recorded in a file's contents, if they are valid JSON
+    pub fn stored_version(&self, contents: &str) -> Option<u32> {
+        serde_json::from_str::<Value>(contents)
+            .ok()
+            .map(|value| self.version_of(&value))
+    }
+
+    fn newer_error(&self, version: u32) -> String {
+        format!(
+            "The {} was saved by a newer version of the app (schema {}, this version supports {})",
+            self.what,
+            version,
+            self.current()
+        )
+    }
+
+    /// Apply the migrations after `from` and stamp the current version
+    fn upgrade(&self, value: &mut Value, from: u32, project_path: &Path) -> Result<(), String> {
+        for (version, migrate) in self.migrations.iter().enumerate().skip(from as usize) {
+            migrate(value, project_path).map_err(|e| {
+                format!(
+                    "Failed to upgrade {} from schema {}: {}",
+                    self.what, version, e
+                )
+            })?;
+        }
+        object(value)?.insert(self.key.to_string(), Value::from(self.current()));
+        Ok(())
+    }
+}
+
+pub const PROJECT_META: Schema = Schema {
+    what: "metadata",
+    key: "schema_version",
+    migrations: &[project_meta_v1, project_meta_v2],
+};
+
+pub const EDITOR_STATE: Schema = Schema {
+    what: "editor state",
+    key: "schemaVersion",
+    migrations: &[editor_state_v1, editor_state_v2],
+};
+
+pub const SCENE_INDEX: Schema = Schema {
+    what: "scene index",
+    key: "schemaVersion",
+    migrations: &[scene_index_v1],
+};
+
+pub const SCENE_MARKDOWN_VERSION: u32 = 1;
+const SCENE_MARKDOWN_MARKER: &str = "<!-- schema:";
+
+fn object(value: &mut Value) -> Result<&mut Map<String, Value>, String> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| "expected a JSON object".to_string())
+}
+
+/// Version 1 adds the version field and fills in required fields that early
+/// builds could leave out
+fn project_meta_v1(value: &mut Value, _project_path: &Path) -> Result<(), String> {
+    let meta = object(value)?;
+    meta.entry("videos")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    meta.entry("path").or_insert_with(|| Value::from(""));
+    meta.entry("created_at").or_insert_with(|| Value::from(0));
+    Ok(())
+}
+
+/// Version 1 adds the version field and derives clip timings missing from
+/// clips saved by early builds
+fn editor_state_v1(value: &mut Value, _project_path: &Path) -> Result<(), String> {
+    let state = object(value)?;
+    state
+        .entry("clips")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    state.entry("selectedClipId").or_insert(Value::Null);
+    state.entry("previewVideoPath").or_insert(Value::Null);
+
+    let clips = state
+        .get_mut("clips")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| "clips is not a list".to_string())?;
+
+    let mut position = 0.0;
+    for clip in clips {
+        let clip = object(clip)?;
+        let number = |clip: &Map<String, Value>, key: &str| clip.get(key).and_then(Value::as_f64);
+
+        let trim_start = number(clip, "trimStart").unwrap_or(0.0);
+        let trim_end = number(clip, "trimEnd")
+            .or_else(|| number(clip, "duration").map(|d| trim_start + d))
+            .or_else(|| number(clip, "originalDuration"))
+            .ok_or_else(|| "clip has no duration".to_string())?;
+        let duration = number(clip, "duration").unwrap_or(trim_end - trim_start);
+
+        clip.entry("trimStart")
+            .or_insert_with(|| Value::from(trim_start));
+        clip.entry("trimEnd")
+            .or_insert_with(|| Value::from(trim_end));
+        clip.entry("duration")
+            .or_insert_with(|| Value::from(duration));
+        clip.entry("originalDuration")
+            .or_insert_with(|| Value::from(trim_end));
+        clip.entry("position")
+            .or_insert_with(|| Value::from(position));
+        clip.entry("createdAt").or_insert_with(|| Value::from(0));
+        clip.entry("thumbnail").or_insert(Value::Null);
+        clip.entry("transitionType").or_insert(Value::Null);
+        clip.entry("transitionDuration").or_insert(Value::Null);
+
+        position = number(clip, "position").unwrap_or(position) + duration;
+    }
+    Ok(())
+}
+
+/// Version 1 wraps the bare list of scenes in an object that can hold the version
+fn scene_index_v1(value: &mut Value, _project_path: &Path) -> Result<(), String> {
+    if value.is_array() {
+        let scenes = value.take();
+        let mut index = Map::new();
+        index.insert("scenes".to_string(), scenes);
+        *value = Value::Object(index);
+    }
+    Ok(())
+}
+
+/// Project-relative form of an absolute path stored by an older version.
+///
+/// Paths into a previous location of the project, e.g. before the workspace
+/// moved, are matched by the longest trailing part that exists in the project.
+/// Files that still exist outside the project, such as referenced imports, are
+/// left alone even if a file with the same trailing path is in the project.
+fn relative_to_project(project_path: &Path, old_root: Option<&Path>, stored: &str) -> String {
+    let path = Path::new(stored);
+    if !path.is_absolute() {
+        return stored.to_string();
+    }
+
+    let roots = [Some(project_path), old_root];
+    let rest = roots
+        .into_iter()
+        .flatten()
+        .find_map(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .or_else(|| {
+            if path.exists() {
+                return None;
+            }
+            let components: Vec<_> = path.components().collect();
+            (1..components.len())
+                .map(|start| components[start..].iter().collect::<PathBuf>())
+                .find(|rest| project_path.join(rest).exists())
+        });
+
+    match rest {
+        Some(rest) => {
+            ProjectPaths::new(project_path).relativize(&project_path.join(rest).to_string_lossy())
+        }
+        None => stored.to_string(),
+    }
+}
+
+/// Version 2 stores paths inside the project relative to its root, and no
+/// longer stores the root itself
+fn project_meta_v2(value: &mut Value, project_path: &Path) -> Result<(), String> {
+    let meta = object(value)?;
+    let old_root = meta
+        .remove("path")
+        .and_then(|path| path.as_str().map(PathBuf::from));
+
+    let Some(imports) = meta
+        .get_mut("imported_videos")
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(());
+    };
+    for record in imports.iter_mut().filter_map(Value::as_object_mut) {
+        if record.get("reference").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        if let Some(file_path) = record.get("file_path").and_then(Value::as_str) {
+            let relative = relative_to_project(project_path, old_root.as_deref(), file_path);
+            record.insert("file_path".to_string(), Value::from(relative));
+        }
+    }
+    Ok(())
+}
+
+/// Version 2 stores clip and preview paths inside the project relative to its root
+fn editor_state_v2(value: &mut Value, project_path: &Path) -> Result<(), String> {
+    let state = object(value)?;
+    if let Some(preview) = state.get("previewVideoPath").and_then(Value::as_str) {
+        let relative = relative_to_project(project_path, None, preview);
+        state.insert("previewVideoPath".to_string(), Value::from(relative));
+    }
+
+    let Some(clips) = state.get_mut("clips").and_then(Value::as_array_mut) else {
+        return Ok(());
+    };
+    for clip in clips.iter_mut().filter_map(Value::as_object_mut) {
+        if let Some(video_path) = clip.get("videoPath").and_then(Value::as_str) {
+            let relative = relative_to_project(project_path, None, video_path);
+            clip.insert("videoPath".to_string(), Value::from(relative));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a file of the given kind, upgrading it in memory to the current schema
+pub fn parse_upgraded<T: DeserializeOwned>(
+    schema: &Schema,
+    project_path: &Path,
+    contents: &str,
+) -> Result<T, String> {
+    let mut value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let version = schema.version_of(&value);
+    if version > schema.current() {
+        return Err(schema.newer_error(version));
+    }
+    schema.upgrade(&mut value, version, project_path)?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Load a project file, upgrading it on disk first if it uses an older schema.
+///
+/// Files from a newer version of the app are refused rather than restored
+/// from backup, so their contents are never lost.
+pub fn load_json<T: DeserializeOwned>(
+    project_path: &Path,
+    path: &Path,
+    schema: &Schema,
+) -> Result<T, String> {
+    let loaded = read_recovering(project_path, path, schema.what, |contents| {
+        let mut value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        let version = schema.version_of(&value);
+        if version > schema.current() {
+            return Ok(Err(schema.newer_error(version)));
+        }
+        schema.upgrade(&mut value, version, project_path)?;
+        let parsed: T = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        Ok(Ok((parsed, version, value)))
+    })?;
+    let (parsed, version, value) = loaded?;
+
+    if version < schema.current() {
+        let json = serde_json::to_string_pretty(&value)
+            .map_err(|e| format!("Failed to serialize {}: {}", schema.what, e))?;
+        persist_upgrade(project_path, path, version, json)?;
+    }
+    Ok(parsed)
+}
+
+/// The schema version recorded in scene markdown, 0 if it has none
+pub fn scene_markdown_version(contents: &str) -> u32 {
+    contents
+        .lines()
+        .next()
+        .and_then(|line| line.trim().strip_prefix(SCENE_MARKDOWN_MARKER))
+        .and_then(|rest| rest.trim_end_matches("-->").trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// The version comment that starts every scene file
+pub fn scene_markdown_header() -> String {
+    format!("{} {} -->\n", SCENE_MARKDOWN_MARKER, SCENE_MARKDOWN_VERSION)
+}
+
+/// Refuse scene markdown from a newer app and stamp older files with the current version
+pub fn upgrade_scene_markdown(
+    project_path: &Path,
+    path: &Path,
+    contents: &str,
+) -> Result<(), String> {
+    let version = scene_markdown_version(contents);
+    if version > SCENE_MARKDOWN_VERSION {
+        return Err(format!(
+            "The scene was saved by a newer version of the app (schema {}, this version supports {})",
+            version, SCENE_MARKDOWN_VERSION
+        ));
+    }
+    if version == SCENE_MARKDOWN_VERSION {
+        return Ok(());
+    }
+
+    // Version 1 only adds the header
+    let upgraded = format!("{}{}", scene_markdown_header(), contents);
+    persist_upgrade(project_path, path, version, upgraded)
+}
+
+/// Keep the original file under `.sixseven/migrations/` and write the upgraded one
+fn persist_upgrade(
+    project_path: &Path,
+    path: &Path,
+    from_version: u32,
+    contents: String,
+) -> Result<(), String> {
+    let _lock = lock_project(project_path)?;
+    let relative = path.strip_prefix(project_path).unwrap_or(path);
+    let original = ProjectPaths::new(project_path)
+        .migrations_dir(from_version)
+        .join(relative);
+
+    // Only the first original is kept if the same file is upgraded again
+    if !original.exists() {
+        if let Some(parent) = original.parent() {
+            ensure_dir(parent)?;
+        }
+        fs::copy(path, &original).map_err(|e| {
+            format!(
+                "Failed to back up {} before upgrading: {}",
+                relative.display(),
+                e
+            )
+        })?;
+    }
+
+    write_with_backup(path, contents)?;
+    eprintln!(
+        "Upgraded {} from schema {}",
+        relative.display(),
+        from_version
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TempProject;
+    use super::*;
+    use serde_json::json;
+    use std::env;
+
+    fn elsewhere(parts: &[&str]) -> String {
+        let path = parts
+            .iter()
+            .fold(env::temp_dir(), |path, part| path.join(part));
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn relative_paths_are_already_in_project_form() {
+        let project = TempProject::new();
+        assert_eq!(
+            relative_to_project(project.root(), None, "videos/a.mp4"),
+            "videos/a.mp4"
+        );
+    }
+
+    #[test]
+    fn paths_under_the_project_or_its_old_root_become_relative() {
+        let project = TempProject::new();
+        let old_root = env::temp_dir().join("old workspace").join("Film");
+        let in_old_root = elsewhere(&["old workspace", "Film", "images", "a.jpg"]);
+
+        assert_eq!(
+            relative_to_project(project.root(), None, &project.path("videos/a.mp4")),
+            "videos/a.mp4"
+        );
+        assert_eq!(
+            relative_to_project(project.root(), Some(&old_root), &in_old_root),
+            "images/a.jpg"
+        );
+    }
+
+    #[test]
+    fn paths_into_a_moved_project_match_files_that_exist() {
+        let project = TempProject::new();
+        project.write(project.video_file("a.mp4"), b"");
+
+        let moved = elsewhere(&["moved", "Film", "videos", "a.mp4"]);
+        assert_eq!(
+            relative_to_project(project.root(), None, &moved),
+            "videos/a.mp4"
+        );
+
+        let missing = elsewhere(&["moved", "Film", "videos", "b.mp4"]);
+        assert_eq!(relative_to_project(project.root(), None, &missing), missing);
+    }
+
+    #[test]
+    fn files_that_exist_outside_the_project_keep_their_path() {
+        let project = TempProject::new();
+        let footage = TempProject::new();
+        project.write(project.video_file("a.mp4"), b"");
+        let referenced = footage.path("videos/a.mp4");
+        footage.write(&referenced, b"");
+
+        assert_eq!(
+            relative_to_project(project.root(), None, &referenced),
+            referenced
+        );
+    }
+
+    #[test]
+    fn project_meta_v2_drops_the_root_and_relativizes_copied_imports() {
+        let project = TempProject::new();
+        let old_root = elsewhere(&["old workspace", "Film"]);
+        let copied = elsewhere(&["old workspace", "Film", "videos", "a"]);
+        let referenced = elsewhere(&["Footage", "b.mp4"]);
+        let mut meta = json!({
+            "path": old_root,
+            "imported_videos": [
+                { "file_path": copied, "reference": false },
+                { "file_path": referenced, "reference": true },
+            ],
+        });
+
+        project_meta_v2(&mut meta, project.root()).unwrap();
+
+        assert!(meta.get("path").is_none());
+        assert_eq!(meta["imported_videos"][0]["file_path"], "videos/a");
+        assert_eq!(meta["imported_videos"][1]["file_path"], referenced.as_str());
+    }
+
+    #[test]
+    fn editor_state_v2_relativizes_clip_and_preview_paths() {
+        let project = TempProject::new();
+        let external = elsewhere(&["Footage", "b.mp4"]);
+        let mut state = json!({
+            "previewVideoPath": project.path("temp/preview.mp4"),
+            "clips": [
+                { "videoPath": project.path("videos/a.mp4") },
+                { "videoPath": external },
+            ],
+        });
+
+        editor_state_v2(&mut state, project.root()).unwrap();
+
+        assert_eq!(state["previewVideoPath"], "temp/preview.mp4");
+        assert_eq!(state["clips"][0]["videoPath"], "videos/a.mp4");
+        assert_eq!(state["clips"][1]["videoPath"], external.as_str());
+    }
+
+    #[test]
+    fn unversioned_editor_state_is_upgraded_to_the_current_schema() {
+        let project = TempProject::new();
+        let contents = json!({
+            "clips": [{ "id": "c1", "videoPath": project.path("videos/a.mp4"), "duration": 4.0 }],
+        })
+        .to_string();
+
+        let upgraded: Value = parse_upgraded(&EDITOR_STATE, project.root(), &contents).unwrap();
+
+        assert_eq!(upgraded["schemaVersion"], EDITOR_STATE.current());
+        assert_eq!(upgraded["clips"][0]["videoPath"], "videos/a.mp4");
+        assert_eq!(upgraded["clips"][0]["trimEnd"], 4.0);
+        assert_eq!(upgraded["clips"][0]["position"], 0.0);
+    }
+
+    #[test]
+    fn files_from_a_newer_schema_are_refused() {
+        let project = TempProject::new();
+        let contents = json!({ "schemaVersion": EDITOR_STATE.current() + 1 }).to_string();
+        let parsed = parse_upgraded::<Value>(&EDITOR_STATE, project.root(), &contents);
+        assert!(parsed.unwrap_err().contains("newer version"));
+    }
+}
@@ -1,6 +1,21 @@
+pub mod archive;
 mod commands;
 pub mod filesystem;
+pub mod history;
+pub mod library;
+pub mod lineage;
+pub mod migrations;
 pub mod paths;
+pub mod recycle;
+pub mod relocate;
+pub mod repair;
 pub mod scenes;
+pub mod storage;
+pub mod templates;
+#[cfg(test)]
+mod test_support;
 pub mod types;
+pub mod validation;
+pub mod versions;
+pub mod workspaces;
 pub use commands::*;
@@ -8,11 +8,23 @@
 //! ~/sixsevenstudio/projects/<project_name>/
 //! ├── .sixseven/
 //! │   ├── metadata.json
-//! │   └── editor_state.json
+//! │   ├── editor_state.json
+//! │   ├── recovery_log.json
+//! │   ├── migrations/
+//! │   │   └── v<version>/
+//! │   ├── history/
+//! │   │   ├── history.json
+//! │   │   └── blobs/
+//! │   └── versions/
+//! │       └── <version_id>/
+//! │           ├── version.json
+//! │           └── files/
 //! ├── images/
 //! │   └── scene_*_reference.jpg
 //! ├── videos/
 //! │   └── <video_id>.mp4
+//! ├── temp/
+//! ├── branding/
 //! └── storyboard/
 //!     ├── context.md
 //!     └── scenes/
@@ -29,6 +41,7 @@ use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 
 use super::filesystem::{ensure_dir, sanitize_project_name};
+use super::workspaces;
 
 const WORKSPACE_FOLDER: &str = "sixsevenstudio";
 const PROJECT_META_DIR: &str = ".sixseven";
@@ -42,8 +55,21 @@ const SCENES_FOLDER: &str = "scenes";
 const SCENES_INDEX_FILE: &str = "index.json";
 const SCENE_FILE: &str = "scene.md";
 const CACHE_FOLDER: &str = "cache";
+const TEMP_FOLDER: &str = "temp";
+const BRANDING_FOLDER: &str = "branding";
 const WAVEFORMS_FOLDER: &str = "waveforms";
 const SPRITES_FOLDER: &str = "sprites";
+const PROXIES_FOLDER: &str = "proxies";
+const LIBRARY_INDEX_FILE: &str = "library_index.json";
+const RECOVERY_LOG_FILE: &str = "recovery_log.json";
+const LOCK_FILE: &str = "project.lock";
+const MIGRATIONS_FOLDER: &str = "migrations";
+const HISTORY_FOLDER: &str = "history";
+const HISTORY_FILE: &str = "history.json";
+const HISTORY_BLOBS_FOLDER: &str = "blobs";
+const VERSIONS_FOLDER: &str = "versions";
+const VERSION_FILE: &str = "version.json";
+const VERSION_FILES_FOLDER: &str = "files";
 
 pub struct ProjectPaths {
     root: PathBuf,
@@ -57,7 +83,8 @@ impl ProjectPaths {
         }
     }
 
-    fn default_workspace_dir() -> PathBuf {
+    /// ~/sixsevenstudio
+    pub fn default_workspace_dir() -> PathBuf {
         let home = env::var("HOME")
             .or_else(|_| env::var("USERPROFILE"))
             .ok()
@@ -66,11 +93,9 @@ impl ProjectPaths {
         home.join(WORKSPACE_FOLDER)
     }
 
-    /// ~/sixsevenstudio
-    pub fn workspace(_app: &AppHandle) -> Result<PathBuf, String> {
-        let workspace = Self::default_workspace_dir();
-        ensure_dir(&workspace)?;
-        Ok(workspace)
+    /// The active workspace, ~/sixsevenstudio unless another is chosen in settings
+    pub fn workspace(app: &AppHandle) -> Result<PathBuf, String> {
+        workspaces::active_workspace(app)
     }
 
     pub fn from_name(app: &AppHandle, project_name: &str) -> Result<Self, String> {
@@ -101,6 +126,31 @@ impl ProjectPaths {
         &self.root
     }
 
+    /// Form of `path` stored in project files: relative to the project root
+    /// with `/` separators when inside the project, otherwise unchanged
+    pub fn relativize(&self, path: &str) -> String {
+        match Path::new(path).strip_prefix(&self.root) {
+            Ok(rest) => rest
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => path.to_string(),
+        }
+    }
+
+    /// Absolute path for a path stored in project files
+    pub fn resolve(&self, stored: &str) -> String {
+        if Path::new(stored).is_absolute() {
+            return stored.to_string();
+        }
+        stored
+            .split('/')
+            .fold(self.root.clone(), |path, part| path.join(part))
+            .to_string_lossy()
+            .to_string()
+    }
+
     pub fn metadata_dir(&self) -> PathBuf {
         self.root.join(PROJECT_META_DIR)
     }
@@ -114,6 +164,16 @@ impl ProjectPaths {
         self.metadata_dir().join(EDITOR_STATE_FILE)
     }
 
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/project.lock
+    pub fn lock_file(&self) -> PathBuf {
+        self.metadata_dir().join(LOCK_FILE)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/recovery_log.json
+    pub fn recovery_log_file(&self) -> PathBuf {
+        self.metadata_dir().join(RECOVERY_LOG_FILE)
+    }
+
     /// ~/sixsevenstudio/projects/<project_name>/images/
     pub fn images_dir(&self) -> PathBuf {
         self.root.join(IMAGES_FOLDER)
@@ -164,6 +224,16 @@ impl ProjectPaths {
         self.scene_dir(scene_id).join(SCENE_FILE)
     }
 
+    /// ~/sixsevenstudio/projects/<project_name>/temp/
+    pub fn temp_dir(&self) -> PathBuf {
+        self.root.join(TEMP_FOLDER)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/branding/
+    pub fn branding_dir(&self) -> PathBuf {
+        self.root.join(BRANDING_FOLDER)
+    }
+
     /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/
     pub fn cache_dir(&self) -> PathBuf {
         self.metadata_dir().join(CACHE_FOLDER)
@@ -176,7 +246,14 @@ impl ProjectPaths {
 
     /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/waveforms/<clip_id>_waveform_<width>.png
     pub fn waveform_file(&self, clip_id: &str, width: u32) -> PathBuf {
-        self.waveforms_dir().join(format!("{}_waveform_{}.png", clip_id, width))
+        self.waveforms_dir()
+            .join(format!("{}_waveform_{}.png", clip_id, width))
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/waveforms/<video_name>_peaks.json
+    pub fn peaks_file(&self, video_name: &str) -> PathBuf {
+        self.waveforms_dir()
+            .join(format!("{}_peaks.json", video_name))
     }
 
     /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/sprites/
@@ -186,6 +263,128 @@ impl ProjectPaths {
 
     /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/sprites/<clip_id>_sprite_<width>.png
     pub fn sprite_file(&self, clip_id: &str, width: u32) -> PathBuf {
-        self.sprites_dir().join(format!("{}_sprite_{}.png", clip_id, width))
+        self.sprites_dir()
+            .join(format!("{}_sprite_{}.png", clip_id, width))
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/proxies/
+    pub fn proxies_dir(&self) -> PathBuf {
+        self.cache_dir().join(PROXIES_FOLDER)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/proxies/<proxy_key>_proxy.mp4
+    ///
+    /// See `ProjectMeta::proxy_key` for what names a video's proxy.
+    pub fn proxy_file(&self, proxy_key: &str) -> PathBuf {
+        self.proxies_dir().join(format!("{}_proxy.mp4", proxy_key))
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/cache/library_index.json
+    pub fn library_index_file(&self) -> PathBuf {
+        self.cache_dir().join(LIBRARY_INDEX_FILE)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/migrations/v<version>/
+    pub fn migrations_dir(&self, from_version: u32) -> PathBuf {
+        self.metadata_dir()
+            .join(MIGRATIONS_FOLDER)
+            .join(format!("v{}", from_version))
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/history/
+    pub fn history_dir(&self) -> PathBuf {
+        self.metadata_dir().join(HISTORY_FOLDER)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/history/history.json
+    pub fn history_file(&self) -> PathBuf {
+        self.history_dir().join(HISTORY_FILE)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/history/blobs/
+    pub fn history_blobs_dir(&self) -> PathBuf {
+        self.history_dir().join(HISTORY_BLOBS_FOLDER)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/history/blobs/<sha256>
+    pub fn history_blob(&self, hash: &str) -> PathBuf {
+        self.history_blobs_dir().join(hash)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/versions/
+    pub fn versions_dir(&self) -> PathBuf {
+        self.metadata_dir().join(VERSIONS_FOLDER)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/versions/<version_id>/
+    pub fn version_dir(&self, version_id: &str) -> PathBuf {
+        self.versions_dir().join(version_id)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/versions/<version_id>/version.json
+    pub fn version_file(&self, version_id: &str) -> PathBuf {
+        self.version_dir(version_id).join(VERSION_FILE)
+    }
+
+    /// ~/sixsevenstudio/projects/<project_name>/.sixseven/versions/<version_id>/files/
+    pub fn version_files_dir(&self, version_id: &str) -> PathBuf {
+        self.version_dir(version_id).join(VERSION_FILES_FOLDER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> ProjectPaths {
+        ProjectPaths::new(env::temp_dir().join("projects").join("My Film"))
+    }
+
+    #[test]
+    fn relativize_stores_project_files_with_forward_slashes() {
+        let paths = project();
+        let video = paths.videos_dir().join("clip.mp4");
+        assert_eq!(
+            paths.relativize(&video.to_string_lossy()),
+            "videos/clip.mp4"
+        );
+    }
+
+    #[test]
+    fn relativize_keeps_paths_outside_the_project() {
+        let paths = project();
+        let outside = env::temp_dir()
+            .join("projects")
+            .join("My Film 2")
+            .join("clip.mp4");
+        let outside = outside.to_string_lossy().to_string();
+        assert_eq!(paths.relativize(&outside), outside);
+    }
+
+    #[test]
+    fn resolve_joins_stored_paths_onto_the_root() {
+        let paths = project();
+        let expected = paths.videos_dir().join("clip.mp4");
+        assert_eq!(paths.resolve("videos/clip.mp4"), expected.to_string_lossy());
+    }
+
+    #[test]
+    fn resolve_keeps_absolute_paths() {
+        let paths = project();
+        let absolute = env::temp_dir()
+            .join("elsewhere.mp4")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(paths.resolve(&absolute), absolute);
+    }
+
+    #[test]
+    fn resolve_undoes_relativize() {
+        let paths = project();
+        let image = paths
+            .image_file("scene_1_reference.jpg")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(paths.resolve(&paths.relativize(&image)), image);
     }
 }
@@ -0,0 +1,476 @@
+//! The workspace recycle area for deleted projects, scenes, videos and images
+//!
+//! Deleted items are moved to `<workspace>/.recycle/<item_id>/` next to an
+//! `item.json` describing where they came from, so they stay on the same
+//! volume and can be put back. Items older than the retention period set in
+//! the app settings are purged when the recycle area is listed and at startup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
+
+use super::archive::unique_project_name;
+use super::filesystem::{current_timestamp, ensure_dir, update_project_meta, write_atomic};
+use super::history;
+use super::paths::ProjectPaths;
+use super::relocate;
+use super::scenes;
+use super::storage::walk;
+use super::types::{ImportedVideoMeta, RecycledItem, RecycledRecord};
+use super::workspaces::{self, SETTINGS_STORE};
+
+const RECYCLE_FOLDER: &str = ".recycle";
+const ITEM_FILE: &str = "item.json";
+const RETENTION_KEY: &str = "recycle_retention_days";
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// ~/sixsevenstudio/.recycle/
+fn recycle_dir(workspace: &Path) -> PathBuf {
+    workspace.join(RECYCLE_FOLDER)
+}
+
+/// ~/sixsevenstudio/.recycle/<item_id>/
+fn item_dir(workspace: &Path, item_id: &str) -> PathBuf {
+    recycle_dir(workspace).join(item_id)
+}
+
+/// Days recycled items are kept; 0 keeps them until purged by hand
+pub fn retention_days(app: &AppHandle) -> Result<u32, String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    Ok(store
+        .get(RETENTION_KEY)
+        .and_then(|value| value.as_u64())
+        .map(|days| days as u32)
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+pub fn set_retention_days(app: &AppHandle, days: u32) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(RETENTION_KEY, days);
+    store.save().map_err(|e| e.to_string())
+}
+
+fn size_of(path: &Path) -> u64 {
+    if path.is_dir() {
+        let mut files = Vec::new();
+        let _ = walk(path, &mut files);
+        files.iter().map(|(_, size)| size).sum()
+    } else {
+        fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+/// Move `source` into the recycle area of `workspace`
+fn recycle(
+    workspace: &Path,
+    kind: &str,
+    project: Option<String>,
+    source: &Path,
+    record: Option<RecycledRecord>,
+) -> Result<RecycledItem, String> {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid path: {}", source.display()))?;
+    let item = RecycledItem {
+        id: Uuid::new_v4().to_string(),
+        kind: kind.to_string(),
+        name,
+        project,
+        deleted_at: current_timestamp(),
+        size: size_of(source),
+        expires_at: None,
+        record,
+    };
+
+    let dir = item_dir(workspace, &item.id);
+    ensure_dir(&dir)?;
+    let json = serde_json::to_string_pretty(&item)
+        .map_err(|e| format!("Failed to serialize recycled item: {}", e))?;
+    write_atomic(&dir.join(ITEM_FILE), json)?;
+
+    if let Err(e) = fs::rename(source, dir.join(&item.name)) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(format!(
+            "Failed to move {} to the recycle area: {}",
+            kind, e
+        ));
+    }
+    Ok(item)
+}
+
+/// Move a whole project into the recycle area of its workspace
+pub fn recycle_project(project_path: &Path) -> Result<RecycledItem, String> {
+    let workspace = project_path.parent().ok_or("Invalid project path")?;
+    recycle(workspace, "project", None, project_path, None)
+}
+
+/// Move a scene folder, video or image of the project at `project_path` into
+/// the recycle area of its workspace
+pub fn recycle_project_file(
+    project_path: &Path,
+    kind: &str,
+    source: &Path,
+    record: Option<RecycledRecord>,
+) -> Result<RecycledItem, String> {
+    let workspace = project_path.parent().ok_or("Invalid project path")?;
+    let project = project_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string());
+    recycle(workspace, kind, project, source, record)
+}
+
+/// Reject ids that are not a recycled item's UUID, so they can't name a path
+/// outside `.recycle/`
+fn validate_item_id(item_id: &str) -> Result<(), String> {
+    Uuid::parse_str(item_id)
+        .map(|_| ())
+        .map_err(|_| format!("Invalid recycled item id '{}'", item_id))
+}
+
+fn read_item(workspace: &Path, item_id: &str) -> Result<RecycledItem, String> {
+    validate_item_id(item_id)?;
+    let contents = fs::read_to_string(item_dir(workspace, item_id).join(ITEM_FILE))
+        .map_err(|_| format!("Recycled item {} not found", item_id))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse recycled item: {}", e))
+}
+
+/// Items in the recycle area, most recently deleted first
+pub fn list_items(workspace: &Path, retention_days: u32) -> Result<Vec<RecycledItem>, String> {
+    let Ok(entries) = fs::read_dir(recycle_dir(workspace)) else {
+        return Ok(Vec::new());
+    };
+
+    let mut items: Vec<RecycledItem> = entries
+        .flatten()
+        .filter_map(|entry| read_item(workspace, &entry.file_name().to_string_lossy()).ok())
+        .map(|mut item| {
+            item.expires_at = (retention_days > 0)
+                .then(|| item.deleted_at + retention_days as i64 * SECONDS_PER_DAY);
+            item
+        })
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    Ok(items)
+}
+
+/// Where a recycled item goes back to
+fn original_path(workspace: &Path, item: &RecycledItem) -> Result<PathBuf, String> {
+    let Some(project) = &item.project else {
+        return Ok(workspace.join(&item.name));
+    };
+
+    let project_path = workspace.join(project);
+    if !project_path.exists() {
+        return Err(format!(
+            "Project '{}' no longer exists; restore it first",
+            project
+        ));
+    }
+    let paths = ProjectPaths::new(project_path);
+    match item.kind.as_str() {
+        "scene" => Ok(paths.scene_dir(&item.name)),
+        "video" => Ok(paths.videos_dir().join(&item.name)),
+        "image" => Ok(paths.image_file(&item.name)),
+        kind => Err(format!("Unknown recycled item kind '{}'", kind)),
+    }
+}
+
+/// Whether undoing "Delete scene" brought back the recycled scene at
+/// `content`: its scene file is back in the project, other files are not
+fn restored_by_undo(item: &RecycledItem, content: &Path, project_path: &Path) -> bool {
+    if item.kind != "scene" {
+        return false;
+    }
+    let scene_file = ProjectPaths::new(project_path).scene_file(&item.name);
+    let recycled = scene_file.file_name().map(|name| content.join(name));
+    match (fs::read(&scene_file), recycled.map(fs::read)) {
+        (Ok(current), Some(Ok(recycled))) => current == recycled,
+        _ => false,
+    }
+}
+
+/// Move a recycled file or folder at `content` back to `target`
+fn put_back(
+    item: &RecycledItem,
+    content: &Path,
+    target: &Path,
+    project_path: &Path,
+) -> Result<(), String> {
+    if !target.exists() {
+        if let Some(parent) = target.parent() {
+            ensure_dir(parent)?;
+        }
+        return fs::rename(content, target)
+            .map_err(|e| format!("Failed to restore {}: {}", item.kind, e));
+    }
+
+    // Undoing "Delete scene" only brings back the scene file; move back
+    // whatever else the folder held
+    if !restored_by_undo(item, content, project_path) {
+        return Err(format!(
+            "Cannot restore {} '{}': a {} with that name exists",
+            item.kind, item.name, item.kind
+        ));
+    }
+    let entries = fs::read_dir(content)
+        .map_err(|e| format!("Failed to read {}: {}", content.display(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let dest = target.join(entry.file_name());
+        if !dest.exists() {
+            fs::rename(entry.path(), &dest)
+                .map_err(|e| format!("Failed to restore {}: {}", item.kind, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Put a recycled item back where it was deleted from, returning its path.
+///
+/// A restored project whose name has been taken since gets a numbered name;
+/// other items fail if something now sits in their place, except a scene
+/// whose deletion was undone, which gets the rest of its folder back. Restoring
+/// a scene is recorded in the project's undo history.
+pub fn restore_item(workspace: &Path, item_id: &str) -> Result<PathBuf, String> {
+    let item = read_item(workspace, item_id)?;
+    let dir = item_dir(workspace, item_id);
+    let content = dir.join(&item.name);
+
+    let target = if item.kind == "project" {
+        let target = workspace.join(unique_project_name(workspace, &item.name));
+        relocate::rename_project(&content, &target)?;
+        target
+    } else {
+        let target = original_path(workspace, &item)?;
+        let project_path = workspace.join(item.project.as_deref().unwrap_or_default());
+        let restore = || -> Result<(), String> {
+            put_back(&item, &content, &target, &project_path)?;
+            match &item.record {
+                Some(RecycledRecord::GeneratedVideo(video)) => {
+                    update_project_meta(&project_path, |meta| {
+                        if !meta.videos.iter().any(|v| v.id == video.id) {
+                            meta.videos.push(video.clone());
+                        }
+                        Ok(())
+                    })?;
+                }
+                Some(RecycledRecord::ImportedVideo(record)) => {
+                    update_project_meta(&project_path, |meta| {
+                        if !meta.imported_videos.iter().any(|r| r.id == record.id) {
+                            meta.imported_videos.push(ImportedVideoMeta {
+                                file_path: target.to_string_lossy().to_string(),
+                                ..record.clone()
+                            });
+                        }
+                        Ok(())
+                    })?;
+                }
+                None => {}
+            }
+            if item.kind == "scene" {
+                // Appends the restored scene to the index
+                scenes::list_scenes(&project_path)?;
+            }
+            Ok(())
+        };
+
+        if item.kind == "scene" {
+            // Recorded like "Delete scene", so undo and redo stay in step with it
+            let paths = ProjectPaths::new(&project_path);
+            let files = [paths.scene_file(&item.name), paths.scenes_index()];
+            history::record(&project_path, "Restore scene", &files, restore)?;
+        } else {
+            restore()?;
+        }
+        target
+    };
+
+    let _ = fs::remove_dir_all(&dir);
+    Ok(target)
+}
+
+/// Permanently delete a recycled item, or every item when `item_id` is `None`
+pub fn purge(workspace: &Path, item_id: Option<&str>) -> Result<(), String> {
+    let dir = match item_id {
+        Some(item_id) => {
+            read_item(workspace, item_id)?;
+            item_dir(workspace, item_id)
+        }
+        None => recycle_dir(workspace),
+    };
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| format!("Failed to purge: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Purge items kept longer than `retention_days`, returning how many were removed
+pub fn purge_expired(workspace: &Path, retention_days: u32) -> Result<usize, String> {
+    let now = current_timestamp();
+    let mut purged = 0;
+    for item in list_items(workspace, retention_days)? {
+        if item.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            purge(workspace, Some(&item.id))?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+/// Apply the retention policy in every workspace that can be found. Failures
+/// are logged rather than returned so they never block startup.
+pub fn purge_expired_everywhere(app: &AppHandle) {
+    let purge_all = || -> Result<(), String> {
+        let retention_days = retention_days(app)?;
+        for workspace in workspaces::list_workspaces(app)? {
+            let path = Path::new(&workspace.path);
+            if path.is_dir() {
+                purge_expired(path, retention_days)?;
+            }
+        }
+        Ok(())
+    };
+    if let Err(e) = purge_all() {
+        eprintln!("Failed to purge expired recycled items: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::TempProject;
+    use super::*;
+
+    /// A workspace holding the project "Film" with the scene "intro"
+    fn workspace_with_scene() -> (TempProject, ProjectPaths) {
+        let workspace = TempProject::new();
+        let project = ProjectPaths::new(workspace.root().join("Film"));
+        scenes::write_scene(
+            project.root(),
+            "intro",
+            "Intro",
+            "A quiet street",
+            "4s",
+            None,
+        )
+        .unwrap();
+        (workspace, project)
+    }
+
+    fn delete_scene(project: &ProjectPaths) {
+        let files = [project.scene_file("intro"), project.scenes_index()];
+        history::record(project.root(), "Delete scene", &files, || {
+            scenes::delete_scene(project.root(), "intro")
+        })
+        .unwrap();
+    }
+
+    fn scene_ids(project: &ProjectPaths) -> Vec<String> {
+        let scenes = scenes::list_scenes(project.root()).unwrap();
+        scenes.into_iter().map(|scene| scene.id).collect()
+    }
+
+    fn recycle_image(workspace: &TempProject, project: &ProjectPaths, name: &str) -> RecycledItem {
+        let image = project.image_file(name);
+        workspace.write(&image, "image");
+        recycle_project_file(project.root(), "image", &image, None).unwrap()
+    }
+
+    #[test]
+    fn restoring_puts_a_deleted_scene_back() {
+        let (workspace, project) = workspace_with_scene();
+        delete_scene(&project);
+        let items = list_items(workspace.root(), 30).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(scene_ids(&project).is_empty());
+
+        let restored = restore_item(workspace.root(), &items[0].id).unwrap();
+
+        assert_eq!(restored, project.scene_dir("intro"));
+        assert_eq!(scene_ids(&project), ["intro"]);
+        assert!(list_items(workspace.root(), 30).unwrap().is_empty());
+        let log = history::load_history(project.root()).unwrap();
+        assert_eq!(log.entries.last().unwrap().label, "Restore scene");
+    }
+
+    #[test]
+    fn restoring_a_scene_whose_deletion_was_undone_completes_its_folder() {
+        let (workspace, project) = workspace_with_scene();
+        let notes = project.scene_dir("intro").join("notes.txt");
+        workspace.write(&notes, "Shoot at dusk");
+        delete_scene(&project);
+        history::undo(project.root()).unwrap();
+        assert!(project.scene_file("intro").exists() && !notes.exists());
+
+        let item = &list_items(workspace.root(), 30).unwrap()[0];
+        restore_item(workspace.root(), &item.id).unwrap();
+
+        assert_eq!(fs::read_to_string(&notes).unwrap(), "Shoot at dusk");
+        assert_eq!(scene_ids(&project), ["intro"]);
+        assert!(list_items(workspace.root(), 30).unwrap().is_empty());
+    }
+
+    #[test]
+    fn restoring_over_a_different_scene_fails_and_keeps_the_item() {
+        let (workspace, project) = workspace_with_scene();
+        delete_scene(&project);
+        scenes::write_scene(project.root(), "intro", "New intro", "Rain", "8s", None).unwrap();
+
+        let item = &list_items(workspace.root(), 30).unwrap()[0];
+        let error = restore_item(workspace.root(), &item.id).unwrap_err();
+
+        assert!(error.contains("a scene with that name exists"), "{}", error);
+        assert_eq!(list_items(workspace.root(), 30).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn purging_removes_one_item_or_all_of_them() {
+        let (workspace, project) = workspace_with_scene();
+        let first = recycle_image(&workspace, &project, "a.png");
+        recycle_image(&workspace, &project, "b.png");
+        recycle_image(&workspace, &project, "c.png");
+
+        purge(workspace.root(), Some(&first.id)).unwrap();
+        let remaining = list_items(workspace.root(), 30).unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.iter().all(|item| item.id != first.id));
+
+        purge(workspace.root(), None).unwrap();
+        assert!(list_items(workspace.root(), 30).unwrap().is_empty());
+    }
+
+    #[test]
+    fn items_past_the_retention_period_are_purged() {
+        let (workspace, project) = workspace_with_scene();
+        let old = recycle_image(&workspace, &project, "old.png");
+        let recent = recycle_image(&workspace, &project, "recent.png");
+        let aged = RecycledItem {
+            deleted_at: current_timestamp() - 31 * SECONDS_PER_DAY,
+            ..old.clone()
+        };
+        let item_file = item_dir(workspace.root(), &old.id).join(ITEM_FILE);
+        workspace.write(item_file, serde_json::to_string(&aged).unwrap());
+
+        // A retention of 0 days keeps everything
+        assert_eq!(purge_expired(workspace.root(), 0).unwrap(), 0);
+        assert_eq!(purge_expired(workspace.root(), 30).unwrap(), 1);
+
+        let remaining = list_items(workspace.root(), 30).unwrap();
+        let ids: Vec<&str> = remaining.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, [recent.id.as_str()]);
+    }
+
+    #[test]
+    fn item_ids_that_are_not_uuids_are_rejected() {
+        let (workspace, project) = workspace_with_scene();
+
+        for item_id in ["../Film", "Film", ""] {
+            assert!(restore_item(workspace.root(), item_id).is_err());
+            assert!(purge(workspace.root(), Some(item_id)).is_err());
+        }
+        assert!(project.scene_file("intro").exists());
+    }
+}
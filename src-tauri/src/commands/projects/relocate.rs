@@ -0,0 +1,275 @@
+//! Renaming and duplicating projects
+//!
+//! Project files store media inside the project by relative path, but files
+//! from older versions, including copies kept in saved versions and the undo
+//! history, may still hold absolute paths. When a project's folder changes,
+//! those are rebased onto the new location.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::filesystem::{
+    current_timestamp, editor_state_json, ensure_dir, lock_project, update_project_meta,
+    write_with_backup,
+};
+use super::history;
+use super::migrations::{load_json, parse_upgraded, EDITOR_STATE};
+use super::paths::ProjectPaths;
+use super::types::ProjectMeta;
+use super::versions;
+use crate::commands::video_editor::types::EditorState;
+
+/// Point paths inside the old project at the same file in the new one
+pub(crate) fn rebase_path(path: &str, old_root: &Path, new_root: &Path) -> String {
+    match Path::new(path).strip_prefix(old_root) {
+        Ok(rest) => new_root.join(rest).to_string_lossy().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+fn rebase_meta(meta: &mut ProjectMeta, old_root: &Path, new_root: &Path) {
+    meta.path = new_root.to_string_lossy().to_string();
+    // Referenced imports live outside the project and keep their paths
+    for record in meta.imported_videos.iter_mut().filter(|r| !r.reference) {
+        record.file_path = rebase_path(&record.file_path, old_root, new_root);
+    }
+}
+
+fn rebase_editor_state(state: &mut EditorState, old_root: &Path, new_root: &Path) {
+    for clip in &mut state.clips {
+        clip.video_path = rebase_path(&clip.video_path, old_root, new_root);
+    }
+    if let Some(preview) = &state.preview_video_path {
+        state.preview_video_path = Some(rebase_path(preview, old_root, new_root));
+    }
+}
+
+/// Rebase serialized editor state, upgrading it to the current schema and
+/// storing media inside the project relative to it
+fn rebase_editor_state_json(
+    json: &[u8],
+    old_root: &Path,
+    new_root: &Path,
+) -> Result<Vec<u8>, String> {
+    let mut state: EditorState =
+        parse_upgraded(&EDITOR_STATE, new_root, &String::from_utf8_lossy(json))
+            .map_err(|e| format!("Failed to parse editor state: {}", e))?;
+    rebase_editor_state(&mut state, old_root, new_root);
+    editor_state_json(new_root, &state).map(String::into_bytes)
+}
+
+/// Rewrite paths into `old_root` in the files of the project now at `project_path`
+fn rebase_project(project_path: &Path, old_root: &Path) -> Result<(), String> {
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+
+    update_project_meta(project_path, |meta| {
+        rebase_meta(meta, old_root, project_path);
+        Ok(())
+    })?;
+
+    let editor_state_file = paths.editor_state_file();
+    if editor_state_file.exists() {
+        let mut state: EditorState = load_json(project_path, &editor_state_file, &EDITOR_STATE)?;
+        rebase_editor_state(&mut state, old_root, project_path);
+        write_with_backup(&editor_state_file, editor_state_json(project_path, &state)?)?;
+    }
+
+    let rebase = |json: &[u8]| rebase_editor_state_json(json, old_root, project_path);
+    versions::rewrite_versioned_file(project_path, &editor_state_file, rebase)?;
+    history::rewrite_file(project_path, &editor_state_file, rebase)
+}
+
+/// Copy `source` into `target`, leaving out `skipped` paths. Files under
+/// `linked` directories are hard-linked when the file system allows it.
+pub(crate) fn copy_tree(
+    source: &Path,
+    target: &Path,
+    linked: &[PathBuf],
+    skipped: &[PathBuf],
+) -> Result<(), String> {
+    ensure_dir(target)?;
+    let entries =
+        fs::read_dir(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        if skipped.contains(&path) {
+            continue;
+        }
+
+        let dest = target.join(entry.file_name());
+        if path.is_dir() {
+            copy_tree(&path, &dest, linked, skipped)?;
+            continue;
+        }
+
+        let link = linked.iter().any(|dir| path.starts_with(dir));
+        if !(link && fs::hard_link(&path, &dest).is_ok()) {
+            fs::copy(&path, &dest)
+                .map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Move a project to `new_path`, rebasing the paths stored in its files
+pub fn rename_project(old_path: &Path, new_path: &Path) -> Result<(), String> {
+    if new_path.exists() {
+        return Err(format!("Project '{}' already exists", new_path.display()));
+    }
+
+    // Not locked while moving: the open lock file would block the rename on Windows
+    fs::rename(old_path, new_path).map_err(|e| format!("Failed to rename project: {}", e))?;
+    rebase_project(new_path, old_path)
+}
+
+/// Copy a project to `new_path` without its undo history.
+///
+/// With `link_media`, videos, images and cached media are hard-linked rather
+/// than copied, so both projects share the same files on disk.
+pub fn duplicate_project(source: &Path, new_path: &Path, link_media: bool) -> Result<(), String> {
+    if new_path.exists() {
+        return Err(format!("Project '{}' already exists", new_path.display()));
+    }
+
+    let paths = ProjectPaths::new(source);
+    let linked = if link_media {
+        vec![paths.videos_dir(), paths.images_dir(), paths.cache_dir()]
+    } else {
+        Vec::new()
+    };
+    let skipped = [paths.lock_file(), paths.history_dir()];
+
+    // Media can take a while to copy, so only the project files under
+    // `.sixseven/` are copied with the project locked, as one consistent set
+    let new_paths = ProjectPaths::new(new_path);
+    let media_skipped = [&skipped[..], &[paths.metadata_dir()]].concat();
+    let copied = copy_tree(source, new_path, &linked, &media_skipped).and_then(|_| {
+        if !paths.metadata_dir().exists() {
+            return Ok(());
+        }
+        let _lock = lock_project(source)?;
+        copy_tree(
+            &paths.metadata_dir(),
+            &new_paths.metadata_dir(),
+            &[],
+            &skipped,
+        )
+    });
+    if let Err(e) = copied {
+        let _ = fs::remove_dir_all(new_path);
+        return Err(e);
+    }
+
+    rebase_project(new_path, source)?;
+    update_project_meta(new_path, |meta| {
+        meta.created_at = current_timestamp();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::filesystem::{read_editor_state, read_project_meta};
+    use super::super::migrations::PROJECT_META;
+    use super::super::test_support::{clip_json, TempProject};
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rebase_path_moves_only_paths_inside_the_old_root() {
+        let old_root = Path::new("/projects/Old");
+        let new_root = Path::new("/projects/New");
+
+        let moved = rebase_path("/projects/Old/videos/a.mp4", old_root, new_root);
+        assert_eq!(Path::new(&moved), new_root.join("videos").join("a.mp4"));
+        assert_eq!(
+            rebase_path("/footage/a.mp4", old_root, new_root),
+            "/footage/a.mp4"
+        );
+        // A sibling folder sharing the name's prefix is not inside the project
+        assert_eq!(
+            rebase_path("/projects/Older/a.mp4", old_root, new_root),
+            "/projects/Older/a.mp4"
+        );
+        assert_eq!(
+            rebase_path("videos/a.mp4", old_root, new_root),
+            "videos/a.mp4"
+        );
+    }
+
+    #[test]
+    fn copy_tree_skips_listed_paths_and_links_linked_folders() {
+        let source = TempProject::new();
+        let target = TempProject::new();
+        source.write(source.video_file("a.mp4"), "video");
+        source.write(source.context_file(), "Noir");
+        source.write(source.history_file(), "{}");
+
+        let copy = target.root().join("Copy");
+        copy_tree(
+            source.root(),
+            &copy,
+            &[source.videos_dir()],
+            &[source.history_dir()],
+        )
+        .unwrap();
+        let copied = ProjectPaths::new(&copy);
+        assert!(!copied.history_dir().exists());
+        assert_eq!(fs::read_to_string(copied.context_file()).unwrap(), "Noir");
+
+        // Hard-linked files share their contents, copied ones don't
+        source.write(source.video_file("a.mp4"), "edited");
+        source.write(source.context_file(), "Comedy");
+        let video = fs::read_to_string(copied.video_file("a.mp4")).unwrap();
+        assert_eq!(video, "edited");
+        assert_eq!(fs::read_to_string(copied.context_file()).unwrap(), "Noir");
+    }
+
+    #[test]
+    fn duplicates_rebase_absolute_paths_and_leave_out_history() {
+        let source = TempProject::new();
+        let target = TempProject::new();
+        let old_video = source.path("videos/a.mp4");
+        let meta = json!({
+            "schema_version": PROJECT_META.current(),
+            "videos": [],
+            "imported_videos": [{
+                "id": "imported",
+                "file_path": old_video,
+                "reference": false,
+                "size": 5,
+                "modified_at": 0,
+                "hash": "",
+            }],
+            "created_at": 0,
+        });
+        source.write(source.metadata_file(), meta.to_string());
+        source.write(&old_video, "video");
+        source.write_clips(vec![clip_json("a", &old_video, 0.0, 0.0, 4.0)]);
+        let version = versions::save_version(source.root(), "Saved", None).unwrap();
+        source.write(source.history_file(), "{}");
+
+        let copy = target.root().join("Copy");
+        duplicate_project(source.root(), &copy, false).unwrap();
+
+        let copied = ProjectPaths::new(&copy);
+        let new_video = copied.resolve("videos/a.mp4");
+        let meta = read_project_meta(&copy).unwrap();
+        assert_eq!(meta.path, copy.to_string_lossy());
+        assert_eq!(meta.imported_videos[0].file_path, new_video);
+        let state = read_editor_state(&copy).unwrap().unwrap();
+        assert_eq!(state.clips[0].video_path, new_video);
+        assert!(!copied.history_dir().exists());
+
+        // Saved versions are rebased too, and don't point back at the source
+        let key = copied.editor_state_file();
+        let key = key.strip_prefix(&copy).unwrap();
+        let saved = copied.version_files_dir(&version.id).join(key);
+        let saved = fs::read_to_string(saved).unwrap();
+        assert!(!saved.contains(&*source.root().to_string_lossy()));
+        assert!(saved.contains("videos/a.mp4"));
+    }
+}
@@ -0,0 +1,459 @@
+//! Keeping project metadata, media files and the timeline in step
+//!
+//! Deleting a video can leave timeline clips pointing at the removed file;
+//! `guard_timeline_usage` refuses, removes those clips or leaves them, as the
+//! caller chooses. `repair_project` fixes records that disagree with the files
+//! on disk. It never deletes media: video files missing from the metadata are
+//! registered again, and anything else unused is left to
+//! `cleanup_project_storage`. A generated video is recorded before its file is
+//! downloaded, so a record without a file is only dropped when the caller
+//! says that generation has finished.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::filesystem::{
+    editor_state_json, file_fingerprint, hash_file, lock_project, read_editor_state,
+    read_project_meta, update_project_meta, write_with_backup,
+};
+use super::history::{self, relative_path};
+use super::paths::ProjectPaths;
+use super::scenes;
+use super::types::{ImportedVideoMeta, ProjectMeta, RepairAction, RepairReport, VideoMeta};
+use crate::commands::video_editor::timeline::new_clip_id;
+use crate::commands::video_editor::types::{EditorState, TimelineClip};
+
+/// Clips on the current timeline that use `file`
+pub fn timeline_clips_using(project_path: &Path, file: &Path) -> Result<Vec<TimelineClip>, String> {
+    Ok(read_editor_state(project_path)?
+        .map(|state| state.clips)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|clip| Path::new(&clip.video_path) == file)
+        .collect())
+}
+
+/// Clips on the current timeline that deleting the video `video_id` would
+/// leave without media: those using a generated video or a copied import.
+/// Referenced imports stay in place when deleted, so none of their clips are.
+pub fn timeline_clips_using_video(
+    project_path: &Path,
+    video_id: &str,
+) -> Result<Vec<TimelineClip>, String> {
+    let paths = ProjectPaths::new(project_path);
+    let import = read_project_meta(project_path)?
+        .imported_videos
+        .into_iter()
+        .find(|record| record.id == video_id);
+    let file = match import {
+        Some(record) if record.reference => return Ok(Vec::new()),
+        Some(_) => paths.video_file(video_id),
+        None => paths.video_file(&format!("{}.mp4", video_id)),
+    };
+    timeline_clips_using(project_path, &file)
+}
+
+/// Remove the timeline clips that use any of `files` as one undoable edit,
+/// returning the ids of the removed clips
+pub fn remove_timeline_clips(
+    project_path: &Path,
+    files: &HashSet<PathBuf>,
+) -> Result<Vec<String>, String> {
+    let editor_state_file = ProjectPaths::new(project_path).editor_state_file();
+
+    history::record(
+        project_path,
+        "Remove clips of deleted media",
+        std::slice::from_ref(&editor_state_file),
+        || {
+            let Some(mut state) = read_editor_state(project_path)? else {
+                return Ok(Vec::new());
+            };
+
+            let removed: Vec<String> = state
+                .clips
+                .iter()
+                .filter(|clip| files.contains(Path::new(&clip.video_path)))
+                .map(|clip| clip.id.clone())
+                .collect();
+            if removed.is_empty() {
+                return Ok(removed);
+            }
+
+            state.clips.retain(|clip| !removed.contains(&clip.id));
+            if state
+                .selected_clip_id
+                .as_ref()
+                .is_some_and(|id| removed.contains(id))
+            {
+                state.selected_clip_id = None;
+            }
+            write_with_backup(&editor_state_file, editor_state_json(project_path, &state)?)?;
+            Ok(removed)
+        },
+    )
+}
+
+/// Check the timeline before `file` is deleted, returning the ids of removed clips.
+///
+/// With `remove_clips` unset, fails with the names of the clips using the
+/// file. `Some(true)` removes those clips; `Some(false)` leaves them pointing
+/// at the missing file.
+pub fn guard_timeline_usage(
+    project_path: &Path,
+    file: &Path,
+    remove_clips: Option<bool>,
+) -> Result<Vec<String>, String> {
+    match remove_clips {
+        Some(true) => remove_timeline_clips(project_path, &HashSet::from([file.to_path_buf()])),
+        Some(false) => Ok(Vec::new()),
+        None => {
+            let clips = timeline_clips_using(project_path, file)?;
+            if clips.is_empty() {
+                return Ok(Vec::new());
+            }
+            let names: Vec<&str> = clips.iter().map(|clip| clip.name.as_str()).collect();
+            Err(format!(
+                "Video is used by {} clip(s) on the timeline: {}. Delete it with removeClips to remove them as well, or keep them without media",
+                clips.len(),
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+struct Actions<'a> {
+    project_path: &'a Path,
+    actions: Vec<RepairAction>,
+    warnings: Vec<RepairAction>,
+}
+
+impl Actions<'_> {
+    fn push(&mut self, file: &Path, message: String) {
+        self.actions.push(RepairAction {
+            file: relative_path(self.project_path, file),
+            message,
+        });
+    }
+
+    fn warn(&mut self, file: &Path, message: String) {
+        self.warnings.push(RepairAction {
+            file: relative_path(self.project_path, file),
+            message,
+        });
+    }
+}
+
+/// Match the metadata to the files in `videos/`
+fn repair_meta(
+    paths: &ProjectPaths,
+    meta: &mut ProjectMeta,
+    drop_missing_videos: &[String],
+    actions: &mut Actions,
+) -> Result<(), String> {
+    let meta_file = paths.metadata_file();
+
+    meta.videos.retain(|video| {
+        if paths.video_file(&format!("{}.mp4", video.id)).exists() {
+            return true;
+        }
+        if drop_missing_videos.contains(&video.id) {
+            actions.push(
+                &meta_file,
+                format!("Removed video {}: its file is missing", video.id),
+            );
+            return false;
+        }
+        actions.warn(
+            &meta_file,
+            format!(
+                "File for video {} is missing; it may still be generating",
+                video.id
+            ),
+        );
+        true
+    });
+
+    let videos = &meta.videos;
+    meta.selected_takes.retain(|scene_number, video_id| {
+        let valid = videos
+            .iter()
+            .any(|v| &v.id == video_id && v.scene_number == Some(*scene_number));
+        if !valid {
+            actions.push(
+                &meta_file,
+                format!(
+                    "Cleared the selected take for scene {}: video {} is not one of its takes",
+                    scene_number, video_id
+                ),
+            );
+        }
+        valid
+    });
+
+    // Referenced files may be on a drive that is not connected; relink_media handles those
+    meta.imported_videos.retain(|record| {
+        let exists = record.reference || Path::new(&record.file_path).exists();
+        if !exists {
+            actions.push(
+                &meta_file,
+                format!("Removed imported video {}: its file is missing", record.id),
+            );
+        }
+        exists
+    });
+
+    let Ok(entries) = fs::read_dir(paths.videos_dir()) else {
+        return Ok(());
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+
+    for file in files {
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let known = meta
+            .imported_videos
+            .iter()
+            .any(|record| Path::new(&record.file_path) == file);
+        if known || name.starts_with('.') {
+            continue;
+        }
+
+        let (size, modified_at) = file_fingerprint(&file)?;
+        if let Some(id) = name.strip_suffix(".mp4") {
+            if meta.videos.iter().any(|video| video.id == id) {
+                continue;
+            }
+            meta.videos.push(VideoMeta {
+                id: id.to_string(),
+                prompt: String::new(),
+                model: "unknown".to_string(),
+                resolution: String::new(),
+                duration: 0,
+                created_at: modified_at,
+                scene_number: None,
+                scene_title: None,
+                sample_number: None,
+                remixed_from_video_id: None,
+                remix_prompt: None,
+                tags: Vec::new(),
+                rating: None,
+                favorite: false,
+                notes: None,
+            });
+            actions.push(
+                &file,
+                format!(
+                    "Registered video {}, which was missing from the metadata",
+                    id
+                ),
+            );
+        } else if name.starts_with("imported_") && !name.contains('.') {
+            meta.imported_videos.push(ImportedVideoMeta {
+                id: name.clone(),
+                original_name: name.clone(),
+                file_path: file.to_string_lossy().to_string(),
+                reference: false,
+                size,
+                modified_at,
+                hash: hash_file(&file)?,
+                probe: Default::default(),
+                imported_at: modified_at,
+                tags: Vec::new(),
+            });
+            actions.push(
+                &file,
+                format!(
+                    "Registered imported video {}, which was missing from the metadata",
+                    name
+                ),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Fix clips whose media inside the project is gone and clip ids that clash,
+/// returning whether anything changed
+fn repair_timeline(paths: &ProjectPaths, state: &mut EditorState, actions: &mut Actions) -> bool {
+    let state_file = paths.editor_state_file();
+    let count = actions.actions.len();
+
+    let mut clips = Vec::new();
+    let mut clip_ids = HashSet::new();
+    for mut clip in std::mem::take(&mut state.clips) {
+        let media = PathBuf::from(&clip.video_path);
+        // Media outside the project may be on a drive that is not connected
+        if !media.exists() && media.starts_with(paths.root()) {
+            let moved = media
+                .file_name()
+                .map(|name| paths.videos_dir().join(name))
+                .filter(|path| path.exists());
+            match moved {
+                Some(path) => {
+                    actions.push(
+                        &state_file,
+                        format!(
+                            "Relinked clip '{}' to {}",
+                            clip.name,
+                            relative_path(paths.root(), &path)
+                        ),
+                    );
+                    clip.video_path = path.to_string_lossy().to_string();
+                }
+                None => {
+                    actions.push(
+                        &state_file,
+                        format!("Removed clip '{}': its video is missing", clip.name),
+                    );
+                    continue;
+                }
+            }
+        }
+
+        if !clip_ids.insert(clip.id.clone()) {
+            let id = new_clip_id();
+            actions.push(
+                &state_file,
+                format!(
+                    "Gave clip '{}' a new id: {} was used twice",
+                    clip.name, clip.id
+                ),
+            );
+            clip_ids.insert(id.clone());
+            clip.id = id;
+        }
+        clips.push(clip);
+    }
+    state.clips = clips;
+
+    if let Some(selected) = &state.selected_clip_id {
+        if !clip_ids.contains(selected) {
+            actions.push(
+                &state_file,
+                format!(
+                    "Cleared the selection: clip {} is not on the timeline",
+                    selected
+                ),
+            );
+            state.selected_clip_id = None;
+        }
+    }
+
+    actions.actions.len() > count
+}
+
+/// Reconcile the metadata, `videos/`, scene images and the timeline.
+///
+/// With `dry_run` nothing is written and the report lists what would change.
+/// Records of videos without a file are kept, with a warning, unless their id
+/// is in `drop_missing_videos`.
+pub fn repair_project(
+    project_path: &Path,
+    dry_run: bool,
+    drop_missing_videos: &[String],
+) -> Result<RepairReport, String> {
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+    let mut actions = Actions {
+        project_path,
+        actions: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    if dry_run {
+        let mut meta = read_project_meta(project_path)?;
+        repair_meta(&paths, &mut meta, drop_missing_videos, &mut actions)?;
+    } else {
+        update_project_meta(project_path, |meta| {
+            repair_meta(&paths, meta, drop_missing_videos, &mut actions)
+        })?;
+    }
+
+    let index_file = paths.scenes_index();
+    for fix in scenes::repair_scene_index(project_path, dry_run)? {
+        actions.push(&index_file, fix);
+    }
+
+    if let Some(mut state) = read_editor_state(project_path)? {
+        if repair_timeline(&paths, &mut state, &mut actions) && !dry_run {
+            let editor_state_file = paths.editor_state_file();
+            let json = editor_state_json(project_path, &state)?;
+            history::record(
+                project_path,
+                "Repair timeline",
+                std::slice::from_ref(&editor_state_file),
+                || write_with_backup(&editor_state_file, json),
+            )?;
+        }
+    }
+
+    Ok(RepairReport {
+        dry_run,
+        actions: actions.actions,
+        warnings: actions.warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::migrations::PROJECT_META;
+    use super::super::test_support::{clip_json, TempProject};
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_the_clips_a_deleted_video_would_leave_without_media() {
+        let project = TempProject::new();
+        let footage = TempProject::new();
+        let referenced = footage.path("take.mp4");
+        let import = |id: &str, file_path: &str, reference: bool| {
+            json!({
+                "id": id,
+                "file_path": file_path,
+                "reference": reference,
+                "size": 1,
+                "modified_at": 0,
+                "hash": id,
+            })
+        };
+        let meta = json!({
+            "schema_version": PROJECT_META.current(),
+            "videos": [],
+            "imported_videos": [
+                import("imported_copy", "videos/imported_copy", false),
+                import("imported_reference", &referenced, true),
+            ],
+            "created_at": 0,
+        });
+        project.write(project.metadata_file(), meta.to_string());
+        project.write_clips(vec![
+            clip_json("a", "videos/generated.mp4", 0.0, 0.0, 4.0),
+            clip_json("b", "videos/imported_copy", 4.0, 0.0, 4.0),
+            clip_json("c", "videos/generated.mp4", 8.0, 4.0, 8.0),
+            clip_json("d", &referenced, 12.0, 0.0, 4.0),
+        ]);
+
+        let ids = |video_id: &str| -> Vec<String> {
+            timeline_clips_using_video(project.root(), video_id)
+                .unwrap()
+                .into_iter()
+                .map(|clip| clip.id)
+                .collect()
+        };
+        assert_eq!(ids("generated"), ["a", "c"]);
+        assert_eq!(ids("imported_copy"), ["b"]);
+        // Referenced files stay where they are when their import is deleted
+        assert!(ids("imported_reference").is_empty());
+        assert!(ids("unused").is_empty());
+    }
+}
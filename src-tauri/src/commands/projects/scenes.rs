@@ -2,11 +2,15 @@ use std::fs;
 use std::path::Path;
 
 use super::super::image::get_image_path;
-use super::filesystem::{ensure_dir, sanitize_project_name};
+use super::filesystem::{
+    ensure_dir, lock_project, read_recovering, sanitize_project_name, write_with_backup,
+};
+use super::migrations::{load_json, scene_markdown_header, upgrade_scene_markdown, SCENE_INDEX};
 use super::paths::ProjectPaths;
-use super::types::{SceneDetails, SceneSummary};
+use super::recycle;
+use super::types::{SceneDetails, SceneIndexFile, SceneSummary};
 
-fn scene_reference_image_name(scene_id: &str) -> String {
+pub(crate) fn scene_reference_image_name(scene_id: &str) -> String {
     format!("scene_{}_reference.jpg", scene_id)
 }
 
@@ -23,11 +27,7 @@ fn read_scene_index(project_path: &Path) -> Result<Vec<SceneSummary>, String> {
         return Ok(Vec::new());
     }
 
-    let contents = fs::read_to_string(&index_path)
-        .map_err(|e| format!("Failed to read scene index: {}", e))?;
-
-    let mut entries: Vec<SceneSummary> = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse scene index: {}", e))?;
+    let mut entries = load_json::<SceneIndexFile>(project_path, &index_path, &SCENE_INDEX)?.scenes;
 
     entries.sort_by(|a, b| a.order.cmp(&b.order));
     Ok(entries)
@@ -38,22 +38,27 @@ fn write_scene_index(project_path: &Path, summaries: &[SceneSummary]) -> Result<
 
     let paths = ProjectPaths::new(project_path);
     let index_path = paths.scenes_index();
-    let json = serde_json::to_string_pretty(summaries)
+    let index = SceneIndexFile {
+        schema_version: SCENE_INDEX.current(),
+        scenes: summaries.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&index)
         .map_err(|e| format!("Failed to serialise scene index: {}", e))?;
 
-    fs::write(&index_path, json).map_err(|e| format!("Failed to write scene index: {}", e))
+    write_with_backup(&index_path, json)
 }
 
 fn create_scene_markdown(title: &str, duration: &str, description: &str) -> String {
     format!(
-        "# {}\n\nDuration: {}\n\n{}\n",
+        "{}# {}\n\nDuration: {}\n\n{}\n",
+        scene_markdown_header(),
         title.trim(),
         duration.trim(),
         description.trim_end()
     )
 }
 
-fn parse_scene_markdown(content: &str) -> Result<(String, String, String), String> {
+pub(crate) fn parse_scene_markdown(content: &str) -> Result<(String, String, String), String> {
     let mut title: Option<String> = None;
     let mut duration: Option<String> = None;
     let mut description_lines: Vec<&str> = Vec::new();
@@ -102,7 +107,7 @@ fn parse_scene_markdown(content: &str) -> Result<(String, String, String), Strin
     Ok((title, duration, description))
 }
 
-fn validate_duration(duration: &str) -> Result<(), String> {
+pub(crate) fn validate_duration(duration: &str) -> Result<(), String> {
     if ["4s", "8s", "12s"].contains(&duration) {
         Ok(())
     } else {
@@ -137,9 +142,11 @@ fn build_details_from_disk(project_path: &Path, scene_id: &str) -> Result<SceneD
         return Err(format!("Scene '{}' does not exist", scene_id));
     }
 
-    let content =
-        fs::read_to_string(&scene_path).map_err(|e| format!("Failed to read scene: {}", e))?;
-    let (title, duration, description) = parse_scene_markdown(&content)?;
+    let (contents, (title, duration, description)) =
+        read_recovering(project_path, &scene_path, "scene", |contents| {
+            Ok((contents.to_string(), parse_scene_markdown(contents)?))
+        })?;
+    upgrade_scene_markdown(project_path, &scene_path, &contents)?;
 
     validate_duration(&duration)?;
 
@@ -165,7 +172,9 @@ pub fn read_context(project_path: &Path) -> Result<String, String> {
         return Ok(String::new());
     }
 
-    fs::read_to_string(&path).map_err(|e| format!("Failed to read context: {}", e))
+    read_recovering(project_path, &path, "context", |contents| {
+        Ok(contents.to_string())
+    })
 }
 
 /// Write the global context
@@ -174,12 +183,14 @@ pub fn write_context(project_path: &Path, content: &str) -> Result<(), String> {
     let paths = ProjectPaths::new(project_path);
     let path = paths.context_file();
 
-    fs::write(&path, content).map_err(|e| format!("Failed to write context: {}", e))
+    write_with_backup(&path, content)
 }
 
 /// List all scenes using the index
 pub fn list_scenes(project_path: &Path) -> Result<Vec<SceneSummary>, String> {
-    let mut summaries = read_scene_index(project_path)?;
+    let _lock = lock_project(project_path)?;
+    let indexed = read_scene_index(project_path)?;
+    let mut summaries = indexed.clone();
 
     // Ensure any on-disk scenes missing from the index are appended at the end.
     let paths = ProjectPaths::new(project_path);
@@ -215,10 +226,74 @@ pub fn list_scenes(project_path: &Path) -> Result<Vec<SceneSummary>, String> {
     }
 
     summaries.sort_by(|a, b| a.order.cmp(&b.order));
-    write_scene_index(project_path, &summaries)?;
+    if summaries != indexed {
+        write_scene_index(project_path, &summaries)?;
+    }
     Ok(summaries)
 }
 
+/// Drop index entries whose scene is gone, match reference image flags to the
+/// images on disk and add scenes missing from the index. Returns what was
+/// changed, or with `dry_run` what would be.
+pub(crate) fn repair_scene_index(
+    project_path: &Path,
+    dry_run: bool,
+) -> Result<Vec<String>, String> {
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+    let indexed = read_scene_index(project_path)?;
+    let mut fixes = Vec::new();
+
+    let mut summaries = Vec::new();
+    for summary in &indexed {
+        if !paths.scene_file(&summary.id).exists() {
+            fixes.push(format!(
+                "Removed scene '{}' from the index: its folder is missing",
+                summary.id
+            ));
+            continue;
+        }
+
+        let image_name = scene_reference_image_name(&summary.id);
+        let has_reference_image = get_image_path(project_path, &image_name)?.is_some();
+        if summary.has_reference_image != has_reference_image {
+            fixes.push(if has_reference_image {
+                format!("Scene '{}' has a reference image", summary.id)
+            } else {
+                format!("Scene '{}' no longer has a reference image", summary.id)
+            });
+        }
+        summaries.push(SceneSummary {
+            has_reference_image,
+            ..summary.clone()
+        });
+    }
+
+    if let Ok(entries) = fs::read_dir(paths.scenes_dir()) {
+        let mut unindexed: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|id| paths.scene_file(id).exists())
+            .filter(|id| !indexed.iter().any(|s| &s.id == id))
+            .collect();
+        unindexed.sort();
+        fixes.extend(
+            unindexed
+                .into_iter()
+                .map(|id| format!("Added scene '{}' to the index", id)),
+        );
+    }
+
+    if !dry_run {
+        if summaries != indexed {
+            write_scene_index(project_path, &summaries)?;
+        }
+        // Appends the scenes missing from the index
+        list_scenes(project_path)?;
+    }
+    Ok(fixes)
+}
+
 /// Read a specific scene with full details
 pub fn read_scene(project_path: &Path, scene_id: &str) -> Result<SceneDetails, String> {
     sanitize_project_name(scene_id)?;
@@ -246,6 +321,7 @@ pub fn write_scene(
     sanitize_project_name(scene_id)?;
     validate_duration(duration)?;
 
+    let _lock = lock_project(project_path)?;
     ensure_storyboard_dirs(project_path)?;
 
     let paths = ProjectPaths::new(project_path);
@@ -253,8 +329,7 @@ pub fn write_scene(
     ensure_dir(&scene_dir)?;
 
     let markdown = create_scene_markdown(title, duration, description);
-    fs::write(paths.scene_file(scene_id), markdown)
-        .map_err(|e| format!("Failed to write scene: {}", e))?;
+    write_with_backup(&paths.scene_file(scene_id), markdown)?;
 
     let image_name = scene_reference_image_name(scene_id);
     let has_reference_image = get_image_path(project_path, &image_name)?.is_some();
@@ -287,10 +362,11 @@ pub fn write_scene(
 pub fn delete_scene(project_path: &Path, scene_id: &str) -> Result<(), String> {
     sanitize_project_name(scene_id)?;
 
+    let _lock = lock_project(project_path)?;
     let paths = ProjectPaths::new(project_path);
     let scene_dir = paths.scene_dir(scene_id);
     if scene_dir.exists() {
-        fs::remove_dir_all(&scene_dir).map_err(|e| format!("Failed to delete scene: {}", e))?;
+        recycle::recycle_project_file(project_path, "scene", &scene_dir, None)?;
     }
 
     let mut summaries = read_scene_index(project_path)?;
@@ -300,6 +376,7 @@ pub fn delete_scene(project_path: &Path, scene_id: &str) -> Result<(), String> {
 
 /// Persist a new explicit ordering for scenes
 pub fn reorder_scenes(project_path: &Path, ordered_ids: &[String]) -> Result<(), String> {
+    let _lock = lock_project(project_path)?;
     let mut summaries = read_scene_index(project_path)?;
     if summaries.is_empty() {
         return Ok(());
@@ -0,0 +1,382 @@
+//! Disk usage of a project and cleanup of files nothing refers to
+//!
+//! A video file is in use while project metadata, the timeline or the
+//! timeline of a saved version refers to it. Waveforms and sprites are cached
+//! per clip and fall out of use when their clip leaves the timeline; peaks are
+//! cached per source video and proxies per video content (see
+//! `ProjectMeta::proxy_key`), and both fall out of use when no such video is
+//! left. Everything in `temp/` is left over from rendering. The undo history is
+//! not consulted: undoing past a cleanup can bring back clips whose media is
+//! gone, which `find_missing_media` then reports.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::filesystem::{lock_project, read_editor_state, read_project_meta};
+use super::history::relative_path;
+use super::paths::ProjectPaths;
+use super::types::{CleanupResult, OrphanFile, StorageCategory, StorageReport};
+use super::versions;
+
+/// Report order of the categories
+const CATEGORIES: [&str; 9] = [
+    "generated_videos",
+    "imported_videos",
+    "images",
+    "storyboard",
+    "cache",
+    "temp",
+    "versions",
+    "history",
+    "other",
+];
+
+/// Every file under `dir` with its size, including hidden ones
+pub(crate) fn walk(dir: &Path, files: &mut Vec<(PathBuf, u64)>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let metadata = entry.metadata().map_err(|e| e.to_string())?;
+        if metadata.is_dir() {
+            walk(&path, files)?;
+        } else if metadata.is_file() {
+            files.push((path, metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+/// What the project's files refer to
+struct References {
+    /// Video files kept by metadata or a timeline
+    videos: HashSet<PathBuf>,
+    /// Non-reference imports, counted as imported rather than generated
+    imports: HashSet<PathBuf>,
+    /// File stems of source videos that still exist, for peaks
+    video_names: HashSet<String>,
+    /// Proxy keys of source videos that still exist
+    proxy_keys: HashSet<String>,
+    /// Clips on the current timeline, for waveforms and sprites
+    clip_ids: HashSet<String>,
+    /// The rendered preview in `temp/` that the editor shows and exports from
+    preview: Option<PathBuf>,
+}
+
+fn file_stem(path: &Path) -> Option<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+}
+
+fn references(paths: &ProjectPaths) -> Result<References, String> {
+    let meta = read_project_meta(paths.root())?;
+    let state = read_editor_state(paths.root())?;
+
+    let imports: HashSet<PathBuf> = meta
+        .imported_videos
+        .iter()
+        .filter(|record| !record.reference)
+        .map(|record| PathBuf::from(&record.file_path))
+        .collect();
+
+    let mut videos = versions::referenced_media(paths.root())?;
+    videos.extend(imports.iter().cloned());
+    videos.extend(
+        meta.videos
+            .iter()
+            .map(|video| paths.video_file(&format!("{}.mp4", video.id))),
+    );
+    videos.extend(
+        state
+            .iter()
+            .flat_map(|state| &state.clips)
+            .map(|clip| PathBuf::from(&clip.video_path)),
+    );
+
+    let referenced_imports = meta
+        .imported_videos
+        .iter()
+        .filter(|record| record.reference)
+        .map(|record| PathBuf::from(&record.file_path));
+    let sources: Vec<PathBuf> = videos
+        .iter()
+        .cloned()
+        .chain(referenced_imports)
+        .filter(|path| path.exists())
+        .collect();
+    let video_names = sources.iter().filter_map(|path| file_stem(path)).collect();
+    let proxy_keys = sources
+        .iter()
+        .filter_map(|path| meta.proxy_key(path))
+        .collect();
+
+    let clip_ids = state
+        .iter()
+        .flat_map(|state| &state.clips)
+        .map(|clip| clip.id.clone())
+        .collect();
+
+    let preview = state
+        .and_then(|state| state.preview_video_path)
+        .map(PathBuf::from);
+
+    Ok(References {
+        videos,
+        imports,
+        video_names,
+        proxy_keys,
+        clip_ids,
+        preview,
+    })
+}
+
+fn category(paths: &ProjectPaths, refs: &References, file: &Path) -> &'static str {
+    if file.starts_with(paths.videos_dir()) {
+        if refs.imports.contains(file) {
+            "imported_videos"
+        } else {
+            "generated_videos"
+        }
+    } else if file.starts_with(paths.images_dir()) {
+        "images"
+    } else if file.starts_with(paths.storyboard_dir()) {
+        "storyboard"
+    } else if file.starts_with(paths.cache_dir()) {
+        "cache"
+    } else if file.starts_with(paths.temp_dir()) {
+        "temp"
+    } else if file.starts_with(paths.versions_dir()) {
+        "versions"
+    } else if file.starts_with(paths.history_dir()) {
+        "history"
+    } else {
+        "other"
+    }
+}
+
+/// Why `file` is an orphan, with its kind, or `None` if it is in use
+fn orphan_reason(
+    paths: &ProjectPaths,
+    refs: &References,
+    file: &Path,
+) -> Option<(&'static str, String)> {
+    let name = file.file_name()?.to_string_lossy().to_string();
+    let parent = file.parent()?;
+
+    if parent == paths.videos_dir() {
+        return (!refs.videos.contains(file)).then(|| {
+            (
+                "video",
+                "Not in the project's videos, imports or any timeline".to_string(),
+            )
+        });
+    }
+
+    if file.starts_with(paths.temp_dir()) {
+        return (refs.preview.as_deref() != Some(file))
+            .then(|| ("temp", "Left over from rendering".to_string()));
+    }
+
+    let per_clip = if parent == paths.waveforms_dir() {
+        name.rsplit_once("_waveform_")
+    } else if parent == paths.sprites_dir() {
+        name.rsplit_once("_sprite_")
+    } else {
+        None
+    };
+    if let Some((clip_id, _)) = per_clip {
+        return (!refs.clip_ids.contains(clip_id)).then(|| {
+            (
+                "cache",
+                format!("Clip {} is no longer on the timeline", clip_id),
+            )
+        });
+    }
+
+    let per_video = if parent == paths.waveforms_dir() {
+        name.strip_suffix("_peaks.json").map(|video_name| {
+            let reason = format!("Video {} no longer exists", video_name);
+            (video_name, &refs.video_names, reason)
+        })
+    } else if parent == paths.proxies_dir() {
+        name.strip_suffix("_proxy.mp4").map(|key| {
+            let reason = "No video it was made from is left".to_string();
+            (key, &refs.proxy_keys, reason)
+        })
+    } else {
+        None
+    };
+    if let Some((key, existing, reason)) = per_video {
+        return (!existing.contains(key)).then_some(("cache", reason));
+    }
+
+    None
+}
+
+fn orphans(paths: &ProjectPaths, refs: &References, files: &[(PathBuf, u64)]) -> Vec<OrphanFile> {
+    files
+        .iter()
+        .filter_map(|(file, bytes)| {
+            orphan_reason(paths, refs, file).map(|(kind, reason)| OrphanFile {
+                path: relative_path(paths.root(), file),
+                kind: kind.to_string(),
+                bytes: *bytes,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Bytes used by each kind of project file, and files that could be removed
+pub fn storage_report(project_path: &Path) -> Result<StorageReport, String> {
+    let paths = ProjectPaths::new(project_path);
+    let refs = references(&paths)?;
+    let mut files = Vec::new();
+    walk(project_path, &mut files)?;
+
+    let mut totals: BTreeMap<&str, (u64, usize)> = BTreeMap::new();
+    for (file, bytes) in &files {
+        let total = totals.entry(category(&paths, &refs, file)).or_default();
+        total.0 += bytes;
+        total.1 += 1;
+    }
+    let categories = CATEGORIES
+        .iter()
+        .map(|name| {
+            let (bytes, file_count) = totals.get(name).copied().unwrap_or_default();
+            StorageCategory {
+                category: name.to_string(),
+                bytes,
+                file_count,
+            }
+        })
+        .collect();
+
+    let orphans = orphans(&paths, &refs, &files);
+    Ok(StorageReport {
+        total_bytes: files.iter().map(|(_, bytes)| bytes).sum(),
+        categories,
+        reclaimable_bytes: orphans.iter().map(|orphan| orphan.bytes).sum(),
+        orphans,
+    })
+}
+
+/// Delete orphaned files, or with `dry_run` only list them.
+///
+/// `kinds` limits the cleanup to some of "video", "cache" and "temp".
+pub fn clean_up(
+    project_path: &Path,
+    kinds: Option<&[String]>,
+    dry_run: bool,
+) -> Result<CleanupResult, String> {
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+    let refs = references(&paths)?;
+    let mut files = Vec::new();
+    walk(project_path, &mut files)?;
+
+    let mut removed: Vec<OrphanFile> = orphans(&paths, &refs, &files)
+        .into_iter()
+        .filter(|orphan| kinds.is_none_or(|kinds| kinds.contains(&orphan.kind)))
+        .collect();
+
+    if !dry_run {
+        // Keep going past files that can't be removed, e.g. a preview still playing on Windows
+        removed.retain(|orphan| {
+            let file = orphan
+                .path
+                .split('/')
+                .fold(project_path.to_path_buf(), |path, part| path.join(part));
+            fs::remove_file(file).is_ok()
+        });
+    }
+
+    Ok(CleanupResult {
+        dry_run,
+        reclaimed_bytes: removed.iter().map(|orphan| orphan.bytes).sum(),
+        removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::migrations::PROJECT_META;
+    use super::super::test_support::TempProject;
+    use super::*;
+    use serde_json::json;
+    use std::env;
+
+    fn refs(preview: Option<PathBuf>) -> References {
+        References {
+            videos: HashSet::new(),
+            imports: HashSet::new(),
+            video_names: HashSet::new(),
+            proxy_keys: HashSet::new(),
+            clip_ids: HashSet::new(),
+            preview,
+        }
+    }
+
+    #[test]
+    fn the_saved_preview_is_not_an_orphan() {
+        let paths = ProjectPaths::new(env::temp_dir().join("Film"));
+        let preview = paths.temp_dir().join("preview.mp4");
+        let leftover = paths.temp_dir().join("concat_list.txt");
+        let refs = refs(Some(preview.clone()));
+
+        assert!(orphan_reason(&paths, &refs, &preview).is_none());
+        assert_eq!(
+            orphan_reason(&paths, &refs, &leftover).map(|(kind, _)| kind),
+            Some("temp")
+        );
+    }
+
+    #[test]
+    fn proxies_are_kept_per_video_content_rather_than_file_name() {
+        let project = TempProject::new();
+        let footage = TempProject::new();
+        let (first, second) = (footage.path("a/take.mp4"), footage.path("b/take.mp4"));
+        footage.write(&first, "one");
+        footage.write(&second, "two");
+        let import = |file_path: &str, hash: &str| {
+            json!({
+                "id": hash,
+                "file_path": file_path,
+                "reference": true,
+                "size": 3,
+                "modified_at": 0,
+                "hash": hash,
+            })
+        };
+        let meta = json!({
+            "schema_version": PROJECT_META.current(),
+            "videos": [{
+                "id": "generated",
+                "prompt": "",
+                "model": "sora-2",
+                "resolution": "1280x720",
+                "duration": 4,
+                "created_at": 0,
+            }],
+            "imported_videos": [import(&first, "hash-one"), import(&second, "hash-two")],
+            "created_at": 0,
+        });
+        project.write(project.metadata_file(), meta.to_string());
+        project.write(project.video_file("generated.mp4"), "video");
+        for key in ["generated", "hash-one", "hash-two", "take", "hash-gone"] {
+            project.write(project.proxy_file(key), "proxy");
+        }
+
+        let report = storage_report(project.root()).unwrap();
+
+        let mut orphans: Vec<&str> = report
+            .orphans
+            .iter()
+            .map(|orphan| orphan.path.rsplit('/').next().unwrap())
+            .collect();
+        orphans.sort();
+        assert_eq!(orphans, ["hash-gone_proxy.mp4", "take_proxy.mp4"]);
+    }
+}
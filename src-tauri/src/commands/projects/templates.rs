@@ -0,0 +1,170 @@
+//! Project templates: storyboard skeletons to start new projects from
+//!
+//! A template is a project-like folder in `<workspace>/.templates/<name>/`
+//! holding the storyboard (context and scenes), scene reference images and
+//! branding assets, next to a `template.json` with the editor and export
+//! defaults. Creating a project from a template copies those files and stores
+//! the defaults in the new project's metadata.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::filesystem::{
+    current_timestamp, ensure_dir, lock_project, read_editor_state, read_project_meta,
+    sanitize_project_name, update_project_meta, write_atomic,
+};
+use super::paths::ProjectPaths;
+use super::relocate::copy_tree;
+use super::scenes;
+use super::types::{ProjectDefaults, ProjectTemplate};
+
+const TEMPLATES_FOLDER: &str = ".templates";
+const TEMPLATE_FILE: &str = "template.json";
+
+/// ~/sixsevenstudio/.templates/
+fn templates_dir(workspace: &Path) -> PathBuf {
+    workspace.join(TEMPLATES_FOLDER)
+}
+
+/// ~/sixsevenstudio/.templates/<template_name>/
+fn template_dir(workspace: &Path, name: &str) -> Result<PathBuf, String> {
+    let name =
+        sanitize_project_name(name).map_err(|_| format!("Invalid template name '{}'", name))?;
+    Ok(templates_dir(workspace).join(name))
+}
+
+fn read_template(dir: &Path) -> Result<ProjectTemplate, String> {
+    let contents = fs::read_to_string(dir.join(TEMPLATE_FILE))
+        .map_err(|e| format!("Failed to read template: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse template: {}", e))
+}
+
+/// Templates of `workspace`, sorted by name
+pub fn list_templates(workspace: &Path) -> Result<Vec<ProjectTemplate>, String> {
+    let Ok(entries) = fs::read_dir(templates_dir(workspace)) else {
+        return Ok(Vec::new());
+    };
+
+    let mut templates: Vec<ProjectTemplate> = entries
+        .flatten()
+        .filter_map(|entry| read_template(&entry.path()).ok())
+        .collect();
+    templates.sort_by_key(|template| template.name.to_lowercase());
+    Ok(templates)
+}
+
+/// Copy the template parts of one project-like folder into another: the
+/// storyboard, scene reference images and branding assets
+fn copy_template_files(source: &Path, target: &Path) -> Result<(), String> {
+    let from = ProjectPaths::new(source);
+    let to = ProjectPaths::new(target);
+
+    for (source_dir, target_dir) in [
+        (from.storyboard_dir(), to.storyboard_dir()),
+        (from.branding_dir(), to.branding_dir()),
+    ] {
+        if source_dir.is_dir() {
+            copy_tree(&source_dir, &target_dir, &[], &[])?;
+        }
+    }
+
+    for scene in scenes::list_scenes(source)? {
+        let image_name = scenes::scene_reference_image_name(&scene.id);
+        let image = from.image_file(&image_name);
+        if image.exists() {
+            ensure_dir(&to.images_dir())?;
+            fs::copy(&image, to.image_file(&image_name))
+                .map_err(|e| format!("Failed to copy {}: {}", image.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Defaults of the project at `project_path`; a transition not set
+/// explicitly is taken from the first transition on its timeline
+fn project_defaults(project_path: &Path) -> Result<ProjectDefaults, String> {
+    let mut defaults = read_project_meta(project_path)?.defaults;
+    if defaults.transition_type.is_none() {
+        let transition = read_editor_state(project_path)?
+            .into_iter()
+            .flat_map(|state| state.clips)
+            .find_map(|clip| clip.transition_type.zip(clip.transition_duration));
+        if let Some((transition_type, transition_duration)) = transition {
+            defaults.transition_type = Some(transition_type);
+            defaults.transition_duration = Some(transition_duration);
+        }
+    }
+    Ok(defaults)
+}
+
+/// Save the storyboard, reference images, branding and defaults of the project
+/// at `project_path` as a new template of `workspace`
+pub fn save_as_template(
+    project_path: &Path,
+    workspace: &Path,
+    name: &str,
+    description: Option<String>,
+) -> Result<ProjectTemplate, String> {
+    let dir = template_dir(workspace, name)?;
+    if dir.exists() {
+        return Err(format!("A template named '{}' already exists", name.trim()));
+    }
+
+    let _lock = lock_project(project_path)?;
+    let template = ProjectTemplate {
+        name: name.trim().to_string(),
+        description: description
+            .map(|description| description.trim().to_string())
+            .filter(|description| !description.is_empty()),
+        created_at: current_timestamp(),
+        defaults: project_defaults(project_path)?,
+        source_project: project_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+    };
+
+    // Build the template next to its final place so a failure leaves nothing behind
+    let staging = templates_dir(workspace).join(format!(".{}.saving", template.name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to clear an earlier attempt: {}", e))?;
+    }
+    let result = (|| {
+        ensure_dir(&staging)?;
+        copy_template_files(project_path, &staging)?;
+        let json = serde_json::to_string_pretty(&template)
+            .map_err(|e| format!("Failed to serialize template: {}", e))?;
+        write_atomic(&staging.join(TEMPLATE_FILE), json)?;
+        fs::rename(&staging, &dir).map_err(|e| format!("Failed to save template: {}", e))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result?;
+
+    Ok(template)
+}
+
+/// Copy the template `name` into the new project at `project_path` and
+/// record its defaults in the project metadata
+pub fn apply_template(project_path: &Path, workspace: &Path, name: &str) -> Result<(), String> {
+    let dir = template_dir(workspace, name)?;
+    let template = read_template(&dir).map_err(|_| format!("Template '{}' not found", name))?;
+
+    let _lock = lock_project(project_path)?;
+    copy_template_files(&dir, project_path)?;
+    update_project_meta(project_path, |meta| {
+        meta.template = Some(template.name.clone());
+        meta.defaults = template.defaults.clone();
+        Ok(())
+    })
+}
+
+/// Delete the template `name`. Projects created from it keep their copies.
+pub fn delete_template(workspace: &Path, name: &str) -> Result<(), String> {
+    let dir = template_dir(workspace, name)?;
+    if !dir.join(TEMPLATE_FILE).exists() {
+        return Err(format!("Template '{}' not found", name));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete template: {}", e))
+}
@@ -0,0 +1,83 @@
+//! Fixtures shared by the project modules' tests
+
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::ops::Deref;
+use std::path::Path;
+use uuid::Uuid;
+
+use super::migrations::EDITOR_STATE;
+use super::paths::ProjectPaths;
+
+/// A project folder under the system temp directory, removed when dropped
+pub struct TempProject(ProjectPaths);
+
+impl TempProject {
+    pub fn new() -> Self {
+        let root = env::temp_dir().join(format!("sixseven-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        Self(ProjectPaths::new(root))
+    }
+
+    /// Absolute path of a `/`-separated path inside the project
+    pub fn path(&self, relative: &str) -> String {
+        self.0.resolve(relative)
+    }
+
+    /// Write a file, creating the folders it is in
+    pub fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) {
+        let path = path.as_ref();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Save an editor state holding `clips`, given in their stored JSON form
+    pub fn write_clips(&self, clips: Vec<Value>) {
+        let state = json!({
+            "schemaVersion": EDITOR_STATE.current(),
+            "clips": clips,
+            "selectedClipId": null,
+            "previewVideoPath": null,
+        });
+        self.write(self.0.editor_state_file(), state.to_string());
+    }
+}
+
+impl Deref for TempProject {
+    type Target = ProjectPaths;
+
+    fn deref(&self) -> &ProjectPaths {
+        &self.0
+    }
+}
+
+impl Drop for TempProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.0.root());
+    }
+}
+
+/// A timeline clip in stored form, playing `trim_start..trim_end` of `video_path`
+pub fn clip_json(
+    id: &str,
+    video_path: &str,
+    position: f64,
+    trim_start: f64,
+    trim_end: f64,
+) -> Value {
+    json!({
+        "id": id,
+        "name": format!("Clip {}", id),
+        "videoPath": video_path,
+        "thumbnail": null,
+        "originalDuration": 8.0,
+        "createdAt": 0,
+        "position": position,
+        "trimStart": trim_start,
+        "trimEnd": trim_end,
+        "duration": trim_end - trim_start,
+        "transitionType": null,
+        "transitionDuration": null,
+    })
+}
@@ -1,19 +1,125 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::migrations::PROJECT_META;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProjectSummary {
     pub name: String,
     pub path: String,
     pub created_at: i64,
+    /// Workspace folder the project is in
+    #[serde(default)]
+    pub workspace: String,
+}
+
+impl ProjectSummary {
+    pub fn new(name: String, path: &Path, created_at: i64) -> Self {
+        Self {
+            name,
+            path: path.to_string_lossy().to_string(),
+            created_at,
+            workspace: path
+                .parent()
+                .map(|parent| parent.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProjectMeta {
+    #[serde(default)]
+    pub schema_version: u32,
     pub videos: Vec<VideoMeta>,
+    /// Absolute project folder, filled in when loading and not stored on disk
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub path: String,
     pub created_at: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storyboard_response_id: Option<String>,
+    /// Use low-res proxies for preview, sprites and waveforms (export always uses originals)
+    #[serde(default)]
+    pub use_proxies: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imported_videos: Vec<ImportedVideoMeta>,
+    /// Keeper video id per storyboard scene number
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub selected_takes: BTreeMap<i32, String>,
+    /// Template the project was created from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "ProjectDefaults::is_empty")]
+    pub defaults: ProjectDefaults,
+}
+
+impl ProjectMeta {
+    pub fn new(path: String, created_at: i64) -> Self {
+        Self {
+            schema_version: PROJECT_META.current(),
+            videos: Vec::new(),
+            path,
+            created_at,
+            storyboard_response_id: None,
+            use_proxies: false,
+            imported_videos: Vec::new(),
+            selected_takes: BTreeMap::new(),
+            template: None,
+            defaults: ProjectDefaults::default(),
+        }
+    }
+
+    /// Name of the proxy generated for the media at `video_path`: an import's
+    /// content hash, or the file stem (the video id) of a generated video
+    pub fn proxy_key(&self, video_path: &Path) -> Option<String> {
+        match self
+            .imported_videos
+            .iter()
+            .find(|record| Path::new(&record.file_path) == video_path)
+        {
+            Some(record) => Some(record.proxy_key().to_string()),
+            None => video_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// Settings used where an edit or export does not specify its own, usually
+/// set from the project's template
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectDefaults {
+    /// Container for exports: "mp4", "mov" or "mkv"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    /// Transition between scenes when assembling the storyboard; "none" for hard cuts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_duration: Option<f64>,
+}
+
+impl ProjectDefaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// `template.json` of a project template
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTemplate {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_at: i64,
+    #[serde(default)]
+    pub defaults: ProjectDefaults,
+    /// Project the template was saved from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_project: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -34,10 +140,487 @@ pub struct VideoMeta {
     pub remixed_from_video_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remix_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Star rating from 1 to 5
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub favorite: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// An imported video file; `reference` imports point at the user's file in place
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ImportedVideoMeta {
+    pub id: String,
+    /// File name the video had when it was imported
+    #[serde(default)]
+    pub original_name: String,
+    /// Absolute path of the media file; stored relative to the project when inside it
+    pub file_path: String,
+    pub reference: bool,
+    pub size: u64,
+    pub modified_at: i64,
+    /// SHA-256 of the file contents, used to find it again after it moves
+    pub hash: String,
+    #[serde(default)]
+    pub probe: VideoProbe,
+    #[serde(default)]
+    pub imported_at: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl ImportedVideoMeta {
+    /// Content hash naming the import's proxy, so files that share a name
+    /// don't share a proxy and a relinked file keeps its own
+    pub fn proxy_key(&self) -> &str {
+        if self.hash.is_empty() {
+            &self.id
+        } else {
+            &self.hash
+        }
+    }
+}
+
+/// Stream information read from ffprobe at import time
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct VideoProbe {
+    pub duration: f64,
+    pub width: u32,
+    pub height: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_rate: Option<f64>,
+    pub has_audio: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingMedia {
+    /// Imported video id, when the file belongs to an import record
+    pub video_id: Option<String>,
+    pub path: String,
+    /// "missing" when the file is gone, "changed" when its size or modification time differ
+    pub reason: String,
+    /// Timeline clips that use the file
+    pub clip_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateFile {
+    pub project_name: String,
+    pub path: String,
+    /// Imported video id, when the file belongs to an import record
+    pub video_id: Option<String>,
+}
+
+/// Files across the workspace with identical content
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub files: Vec<DuplicateFile>,
+    /// Bytes that could be reclaimed by keeping a single copy
+    pub redundant_bytes: u64,
+}
+
+/// A generated or imported video as shown in the media library
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryItem {
+    pub id: String,
+    /// "generated" or "imported"
+    pub kind: String,
+    pub title: String,
+    pub prompt: Option<String>,
+    pub model: Option<String>,
+    pub resolution: Option<String>,
+    pub duration: f64,
+    pub scene_number: Option<i32>,
+    pub scene_title: Option<String>,
+    pub created_at: i64,
+    pub tags: Vec<String>,
+    pub video_path: String,
+    /// Lowercased title, prompts and tags used for text search
+    #[serde(default)]
+    pub search_text: String,
+}
+
+/// Cached library entries, keyed by the content hash of the metadata they came from
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LibraryIndex {
+    pub source_hash: String,
+    pub items: Vec<LibraryItem>,
+}
+
+/// Filters for `query_library`; unset fields match everything
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LibraryQuery {
+    /// Whitespace-separated terms that must all appear in the title, prompt or tags
+    pub text: Option<String>,
+    pub kinds: Option<Vec<String>>,
+    pub models: Option<Vec<String>>,
+    pub resolutions: Option<Vec<String>>,
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+    pub scene_numbers: Option<Vec<i32>>,
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+    /// Items must carry every one of these tags
+    pub tags: Option<Vec<String>>,
+    /// "createdAt" (default), "title", "duration" or "scene"
+    pub sort_by: Option<String>,
+    pub descending: Option<bool>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryPage {
+    pub items: Vec<LibraryItem>,
+    /// Matching items before pagination
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// A run of words that are the same, added or removed between two prompts
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PromptDiffSegment {
+    /// "same", "added" or "removed"
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemixNode {
+    pub video_id: String,
+    pub parent_id: Option<String>,
+    /// Remixes made from this video, oldest first
+    pub children: Vec<String>,
+    /// Generations below the tree's root
+    pub depth: usize,
+    /// The remix prompt for remixes, otherwise the original prompt
+    pub prompt: String,
+    /// Changes from the parent's prompt; empty for roots
+    pub prompt_diff: Vec<PromptDiffSegment>,
+    pub created_at: i64,
+    pub scene_number: Option<i32>,
+    pub sample_number: Option<i32>,
+}
+
+/// A remix whose source video is no longer in the project
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedRemix {
+    pub video_id: String,
+    pub missing_parent_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemixLineage {
+    pub roots: Vec<String>,
+    /// Nodes in breadth-first order from the roots
+    pub nodes: Vec<RemixNode>,
+    /// Root to parent of the requested video; empty for whole-project lineage
+    pub ancestors: Vec<String>,
+    /// Every remix descended from the requested video
+    pub descendants: Vec<String>,
+    pub orphans: Vec<OrphanedRemix>,
+}
+
+/// One file touched by a history entry; contents are blob hashes, `None` when the file did not exist
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryChange {
+    /// Path relative to the project root
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: String,
+    pub label: String,
+    pub created_at: i64,
+    pub changes: Vec<HistoryChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryLog {
+    /// Oldest first
+    pub entries: Vec<HistoryEntry>,
+    /// Number of entries currently applied; entries after it can be redone
+    pub cursor: usize,
+}
+
+/// A named copy of the project's editor state and storyboard
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectVersion {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_at: i64,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct VersionedClip {
+    pub id: String,
+    pub name: String,
+    pub video_path: String,
+}
+
+/// A clip present in both versions whose timing or transition changed; pairs are (before, after)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipRetime {
+    pub clip: VersionedClip,
+    pub position: (f64, f64),
+    pub trim_start: (f64, f64),
+    pub trim_end: (f64, f64),
+    pub transition_changed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneChange {
+    pub id: String,
+    pub title: String,
+    /// Changed fields: "title", "duration", "order" or "description"
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDiff {
+    pub clips_added: Vec<VersionedClip>,
+    pub clips_removed: Vec<VersionedClip>,
+    pub clips_retimed: Vec<ClipRetime>,
+    pub scenes_added: Vec<SceneSummary>,
+    pub scenes_removed: Vec<SceneSummary>,
+    pub scenes_changed: Vec<SceneChange>,
+    pub context_changed: bool,
+}
+
+/// A project file that was damaged and restored from its backup
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryEvent {
+    /// Path relative to the project root
+    pub file: String,
+    /// Why the file could not be read
+    pub error: String,
+    pub recovered_at: i64,
+}
+
+/// What to put in a project archive
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArchiveOptions {
+    /// Waveforms, sprites, proxies and the library index
+    pub include_cache: bool,
+    /// Saved versions
+    pub include_versions: bool,
+    /// Copy referenced imports into the archive so it does not depend on this machine
+    pub include_referenced_media: bool,
+    /// Leave out videos that are not used on the timeline
+    pub timeline_media_only: bool,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        Self {
+            include_cache: false,
+            include_versions: true,
+            include_referenced_media: true,
+            timeline_media_only: false,
+        }
+    }
+}
+
+/// A file in a project archive; `path` is relative to the project root
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// `manifest.json` at the root of a project archive
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub project_name: String,
+    pub exported_at: i64,
+    pub files: Vec<ArchiveEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSummary {
+    pub path: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+/// A folder of projects registered in the app settings
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub name: String,
+    pub path: String,
+}
+
+/// Registered workspaces and the one projects are opened from, as stored in the settings
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRegistry {
+    #[serde(default)]
+    pub workspaces: Vec<Workspace>,
+    /// Path of the active workspace; the default workspace when unset
+    #[serde(default)]
+    pub active: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    pub name: String,
+    pub path: String,
+    pub active: bool,
+    pub is_default: bool,
+    /// False when the folder cannot be found, e.g. an unplugged drive
+    pub available: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageCategory {
+    /// "generated_videos", "imported_videos", "images", "storyboard", "cache",
+    /// "temp", "versions", "history" or "other"
+    pub category: String,
+    pub bytes: u64,
+    pub file_count: usize,
+}
+
+/// A file that nothing in the project refers to
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanFile {
+    /// Relative to the project root
+    pub path: String,
+    /// "video", "cache" or "temp"
+    pub kind: String,
+    pub bytes: u64,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub total_bytes: u64,
+    pub categories: Vec<StorageCategory>,
+    pub orphans: Vec<OrphanFile>,
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupResult {
+    /// Nothing was deleted; `removed` lists what would have been
+    pub dry_run: bool,
+    pub removed: Vec<OrphanFile>,
+    pub reclaimed_bytes: u64,
+}
+
+/// Metadata a recycled video had in its project, put back when it is restored
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RecycledRecord {
+    GeneratedVideo(VideoMeta),
+    ImportedVideo(ImportedVideoMeta),
+}
+
+/// Something deleted into the workspace's recycle area
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecycledItem {
+    pub id: String,
+    /// "project", "scene", "video" or "image"
+    pub kind: String,
+    /// File or folder name: the project name, scene id, video file or image file
+    pub name: String,
+    /// Project the item was deleted from; `None` for projects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub deleted_at: i64,
+    pub size: u64,
+    /// When the retention policy purges the item; not stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<RecycledRecord>,
+}
+
+/// A change made by `repair_project`
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairAction {
+    /// Relative to the project root
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    /// Nothing was changed; `actions` lists what would have been
+    pub dry_run: bool,
+    pub actions: Vec<RepairAction>,
+    /// Problems left alone, such as videos that may still be generating
+    pub warnings: Vec<RepairAction>,
+}
+
+/// A problem found by `validate_project`
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    /// "error" when a file cannot be loaded, "warning" when files disagree,
+    /// "info" when a file will be upgraded to the current schema
+    pub severity: String,
+    /// Path relative to the project root
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    /// True when every file can be loaded
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct SceneSummary {
     pub id: String,
     pub title: String,
@@ -56,3 +639,12 @@ pub struct SceneDetails {
     pub has_reference_image: bool,
     pub order: i32,
 }
+
+/// On-disk layout of `storyboard/scenes/index.json`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneIndexFile {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub scenes: Vec<SceneSummary>,
+}
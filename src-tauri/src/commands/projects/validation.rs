@@ -0,0 +1,351 @@
+//! Read-only checks that a project's files load and agree with each other
+
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use super::filesystem::backup_path;
+use super::history::relative_path;
+use super::migrations::{
+    parse_upgraded, scene_markdown_version, Schema, EDITOR_STATE, PROJECT_META, SCENE_INDEX,
+    SCENE_MARKDOWN_VERSION,
+};
+use super::paths::ProjectPaths;
+use super::scenes::{parse_scene_markdown, validate_duration};
+use super::types::{
+    HistoryLog, ProjectMeta, SceneIndexFile, SceneSummary, ValidationIssue, ValidationReport,
+};
+use crate::commands::video_editor::types::EditorState;
+
+/// Trims may overshoot the probed duration by float noise
+const DURATION_TOLERANCE: f64 = 0.05;
+
+struct Issues<'a> {
+    project_path: &'a Path,
+    issues: Vec<ValidationIssue>,
+}
+
+impl Issues<'_> {
+    fn push(&mut self, severity: &str, path: &Path, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: severity.to_string(),
+            file: relative_path(self.project_path, path),
+            message,
+        });
+    }
+
+    fn error(&mut self, path: &Path, message: String) {
+        self.push("error", path, message);
+    }
+
+    fn warning(&mut self, path: &Path, message: String) {
+        self.push("warning", path, message);
+    }
+
+    fn info(&mut self, path: &Path, message: String) {
+        self.push("info", path, message);
+    }
+
+    /// Parse a versioned JSON file without upgrading it on disk
+    fn load<T: DeserializeOwned>(&mut self, path: &Path, schema: &Schema) -> Option<T> {
+        if !path.exists() {
+            return None;
+        }
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.error(path, format!("Failed to read {}: {}", schema.what, e));
+                return None;
+            }
+        };
+
+        match parse_upgraded(schema, self.project_path, &contents) {
+            Ok(value) => {
+                if let Some(version) = schema
+                    .stored_version(&contents)
+                    .filter(|version| *version < schema.current())
+                {
+                    self.info(
+                        path,
+                        format!(
+                            "Uses schema {} and will be upgraded to {} when opened",
+                            version,
+                            schema.current()
+                        ),
+                    );
+                }
+                Some(value)
+            }
+            Err(e) => {
+                let recoverable = fs::read_to_string(backup_path(path)).is_ok_and(|backup| {
+                    parse_upgraded::<T>(schema, self.project_path, &backup).is_ok()
+                });
+                let hint = if recoverable {
+                    " (a valid backup will be restored when opened)"
+                } else {
+                    ""
+                };
+                self.error(
+                    path,
+                    format!("Failed to parse {}: {}{}", schema.what, e, hint),
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Report files that cannot be loaded and references between files that do not line up
+pub fn validate_project(project_path: &Path) -> ValidationReport {
+    let paths = ProjectPaths::new(project_path);
+    let mut issues = Issues {
+        project_path,
+        issues: Vec::new(),
+    };
+
+    let meta_file = paths.metadata_file();
+    if !meta_file.exists() {
+        issues.warning(&meta_file, "Project has no metadata file".to_string());
+    }
+    let meta: Option<ProjectMeta> = issues.load(&meta_file, &PROJECT_META);
+    let state: Option<EditorState> = issues.load(&paths.editor_state_file(), &EDITOR_STATE);
+    let index: Option<SceneIndexFile> = issues.load(&paths.scenes_index(), &SCENE_INDEX);
+
+    let history_file = paths.history_file();
+    if let Ok(contents) = fs::read_to_string(&history_file) {
+        match serde_json::from_str::<HistoryLog>(&contents) {
+            Ok(log) if log.cursor > log.entries.len() => issues.warning(
+                &history_file,
+                "Undo position is past the last history entry".to_string(),
+            ),
+            Ok(_) => {}
+            Err(e) => issues.error(&history_file, format!("Failed to parse history: {}", e)),
+        }
+    }
+
+    let scene_ids = check_scenes(&mut issues, &paths);
+    if let Some(index) = &index {
+        check_scene_index(&mut issues, &paths, &index.scenes, &scene_ids);
+    }
+    if let Some(meta) = &meta {
+        check_meta(&mut issues, &paths, meta);
+    }
+    if let Some(state) = &state {
+        check_editor_state(&mut issues, &paths, state);
+    }
+
+    let valid = !issues.issues.iter().any(|issue| issue.severity == "error");
+    ValidationReport {
+        valid,
+        issues: issues.issues,
+    }
+}
+
+/// Check every scene file, returning the ids of the scene folders on disk
+fn check_scenes(issues: &mut Issues, paths: &ProjectPaths) -> HashSet<String> {
+    let mut scene_ids = HashSet::new();
+    let Ok(entries) = fs::read_dir(paths.scenes_dir()) else {
+        return scene_ids;
+    };
+
+    for entry in entries.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let scene_id = entry.file_name().to_string_lossy().to_string();
+        let scene_file = paths.scene_file(&scene_id);
+        scene_ids.insert(scene_id);
+
+        let contents = match fs::read_to_string(&scene_file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                issues.error(&scene_file, format!("Failed to read scene: {}", e));
+                continue;
+            }
+        };
+
+        let version = scene_markdown_version(&contents);
+        if version > SCENE_MARKDOWN_VERSION {
+            issues.error(
+                &scene_file,
+                format!(
+                    "Saved by a newer version of the app (schema {}, this version supports {})",
+                    version, SCENE_MARKDOWN_VERSION
+                ),
+            );
+            continue;
+        }
+        if version < SCENE_MARKDOWN_VERSION {
+            issues.info(
+                &scene_file,
+                format!(
+                    "Uses schema {} and will be upgraded to {} when opened",
+                    version, SCENE_MARKDOWN_VERSION
+                ),
+            );
+        }
+
+        match parse_scene_markdown(&contents) {
+            Ok((_, duration, _)) => {
+                if let Err(e) = validate_duration(&duration) {
+                    issues.error(&scene_file, e);
+                }
+            }
+            Err(e) => issues.error(&scene_file, format!("Failed to parse scene: {}", e)),
+        }
+    }
+
+    scene_ids
+}
+
+fn check_scene_index(
+    issues: &mut Issues,
+    paths: &ProjectPaths,
+    scenes: &[SceneSummary],
+    scene_ids: &HashSet<String>,
+) {
+    let index_file = paths.scenes_index();
+    let mut seen_ids = HashSet::new();
+    let mut seen_orders = HashSet::new();
+
+    for scene in scenes {
+        if !seen_ids.insert(scene.id.as_str()) {
+            issues.error(&index_file, format!("Scene '{}' is listed twice", scene.id));
+        }
+        if !seen_orders.insert(scene.order) {
+            issues.warning(
+                &index_file,
+                format!(
+                    "Scene '{}' shares position {} with another scene",
+                    scene.id, scene.order
+                ),
+            );
+        }
+        if !scene_ids.contains(&scene.id) {
+            issues.warning(
+                &index_file,
+                format!("Scene '{}' is listed but its folder is missing", scene.id),
+            );
+        }
+    }
+
+    let mut unlisted: Vec<&String> = scene_ids
+        .iter()
+        .filter(|id| !seen_ids.contains(id.as_str()))
+        .collect();
+    unlisted.sort();
+    for scene_id in unlisted {
+        issues.warning(
+            &paths.scene_dir(scene_id),
+            format!("Scene '{}' is not in the scene index", scene_id),
+        );
+    }
+}
+
+fn check_meta(issues: &mut Issues, paths: &ProjectPaths, meta: &ProjectMeta) {
+    let meta_file = paths.metadata_file();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for video in &meta.videos {
+        *counts.entry(video.id.as_str()).or_default() += 1;
+    }
+    let mut duplicates: Vec<(&str, usize)> =
+        counts.into_iter().filter(|(_, count)| *count > 1).collect();
+    duplicates.sort();
+    for (video_id, count) in duplicates {
+        issues.error(
+            &meta_file,
+            format!("Video {} is recorded {} times", video_id, count),
+        );
+    }
+
+    for video in &meta.videos {
+        let video_file = paths.video_file(&format!("{}.mp4", video.id));
+        if !video_file.exists() {
+            issues.warning(
+                &meta_file,
+                format!("File for video {} is missing from videos/", video.id),
+            );
+        }
+    }
+
+    for (scene_number, video_id) in &meta.selected_takes {
+        match meta.videos.iter().find(|v| &v.id == video_id) {
+            None => issues.warning(
+                &meta_file,
+                format!(
+                    "Selected take for scene {} is unknown video {}",
+                    scene_number, video_id
+                ),
+            ),
+            Some(video) if video.scene_number != Some(*scene_number) => issues.warning(
+                &meta_file,
+                format!(
+                    "Selected take for scene {} is video {}, which belongs to another scene",
+                    scene_number, video_id
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    for record in &meta.imported_videos {
+        if !Path::new(&paths.resolve(&record.file_path)).exists() {
+            let message = if record.reference {
+                format!(
+                    "Referenced file for {} is missing: {}",
+                    record.id, record.file_path
+                )
+            } else {
+                format!("File for imported video {} is missing", record.id)
+            };
+            issues.warning(&meta_file, message);
+        }
+    }
+}
+
+fn check_editor_state(issues: &mut Issues, paths: &ProjectPaths, state: &EditorState) {
+    let state_file = paths.editor_state_file();
+    let mut clip_ids = HashSet::new();
+
+    for clip in &state.clips {
+        if !clip_ids.insert(clip.id.as_str()) {
+            issues.error(
+                &state_file,
+                format!("Clip id {} is used more than once", clip.id),
+            );
+        }
+        if !Path::new(&paths.resolve(&clip.video_path)).exists() {
+            issues.warning(
+                &state_file,
+                format!(
+                    "Clip '{}' uses a missing file: {}",
+                    clip.name, clip.video_path
+                ),
+            );
+        }
+        let out_of_range = clip.trim_start < 0.0
+            || clip.trim_start >= clip.trim_end
+            || clip.trim_end > clip.original_duration + DURATION_TOLERANCE;
+        if out_of_range {
+            issues.warning(
+                &state_file,
+                format!(
+                    "Clip '{}' is trimmed to {:.2}s-{:.2}s of a {:.2}s video",
+                    clip.name, clip.trim_start, clip.trim_end, clip.original_duration
+                ),
+            );
+        }
+    }
+
+    if let Some(selected) = &state.selected_clip_id {
+        if !clip_ids.contains(selected.as_str()) {
+            issues.warning(
+                &state_file,
+                format!("Selected clip {} is not on the timeline", selected),
+            );
+        }
+    }
+}
@@ -0,0 +1,628 @@
+//! Named versions of a project's editor state and storyboard
+//!
+//! A version is a copy of `editor_state.json` and every file under
+//! `storyboard/`, stored in `.sixseven/versions/<version_id>/files/` with the
+//! same layout as the project. Media in `videos/` and `images/` is not copied:
+//! restoring keeps using the project's media, and forks hard-link it.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::filesystem::{
+    collect_files, current_timestamp, editor_state_json, ensure_dir, lock_project, write_atomic,
+    write_project_meta, write_with_backup,
+};
+use super::history;
+use super::migrations::{load_json, parse_upgraded, EDITOR_STATE, PROJECT_META, SCENE_INDEX};
+use super::paths::ProjectPaths;
+use super::scenes::parse_scene_markdown;
+use super::types::{
+    ClipRetime, ProjectMeta, ProjectVersion, SceneChange, SceneIndexFile, SceneSummary,
+    VersionDiff, VersionedClip,
+};
+use crate::commands::video_editor::types::{EditorState, TimelineClip};
+
+/// Pseudo version id for the project's current files in diffs
+pub const CURRENT_VERSION: &str = "current";
+
+/// Project files keyed by `/`-separated path relative to the project root
+type Snapshot = BTreeMap<String, Vec<u8>>;
+
+fn relative_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn key_to_path(root: &Path, key: &str) -> PathBuf {
+    key.split('/')
+        .fold(root.to_path_buf(), |path, part| path.join(part))
+}
+
+/// Paths of the versioned files currently in the project
+fn versioned_files(paths: &ProjectPaths) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    if paths.editor_state_file().exists() {
+        files.push(paths.editor_state_file());
+    }
+    if paths.storyboard_dir().exists() {
+        collect_files(&paths.storyboard_dir(), &mut files)?;
+    }
+    Ok(files)
+}
+
+fn read_snapshot(root: &Path, files: &[PathBuf]) -> Result<Snapshot, String> {
+    files
+        .iter()
+        .map(|file| {
+            fs::read(file)
+                .map(|content| (relative_key(root, file), content))
+                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))
+        })
+        .collect()
+}
+
+fn current_snapshot(paths: &ProjectPaths) -> Result<Snapshot, String> {
+    read_snapshot(paths.root(), &versioned_files(paths)?)
+}
+
+/// Reject ids that are not a saved version's UUID, so they can't name a path
+/// outside `versions/`
+pub fn validate_version_id(version_id: &str) -> Result<(), String> {
+    Uuid::parse_str(version_id)
+        .map(|_| ())
+        .map_err(|_| format!("Invalid version id '{}'", version_id))
+}
+
+fn version_snapshot(paths: &ProjectPaths, version_id: &str) -> Result<Snapshot, String> {
+    let files_dir = paths.version_files_dir(version_id);
+    if !paths.version_file(version_id).exists() {
+        return Err(format!("Version {} not found", version_id));
+    }
+
+    // Laid out as the project, so the editor state is under the hidden
+    // `.sixseven/` that walking the whole folder would skip
+    let files = versioned_files(&ProjectPaths::new(&files_dir))?;
+    read_snapshot(&files_dir, &files)
+}
+
+fn load_snapshot(paths: &ProjectPaths, version_id: &str) -> Result<Snapshot, String> {
+    if version_id == CURRENT_VERSION {
+        current_snapshot(paths)
+    } else {
+        version_snapshot(paths, version_id)
+    }
+}
+
+fn read_version(paths: &ProjectPaths, version_id: &str) -> Result<ProjectVersion, String> {
+    let contents = fs::read_to_string(paths.version_file(version_id))
+        .map_err(|_| format!("Version {} not found", version_id))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse version: {}", e))
+}
+
+/// Save the current editor state and storyboard as a named version
+pub fn save_version(
+    project_path: &Path,
+    name: &str,
+    description: Option<String>,
+) -> Result<ProjectVersion, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Version name cannot be empty".to_string());
+    }
+
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+    let version = ProjectVersion {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        description: description.filter(|d| !d.trim().is_empty()),
+        created_at: current_timestamp(),
+    };
+
+    let files_dir = paths.version_files_dir(&version.id);
+    ensure_dir(&files_dir)?;
+    for (key, content) in current_snapshot(&paths)? {
+        let target = key_to_path(&files_dir, &key);
+        if let Some(parent) = target.parent() {
+            ensure_dir(parent)?;
+        }
+        write_atomic(&target, content)?;
+    }
+
+    let json = serde_json::to_string_pretty(&version)
+        .map_err(|e| format!("Failed to serialize version: {}", e))?;
+    write_atomic(&paths.version_file(&version.id), json)?;
+
+    Ok(version)
+}
+
+/// All saved versions, newest first
+pub fn list_versions(project_path: &Path) -> Result<Vec<ProjectVersion>, String> {
+    let paths = ProjectPaths::new(project_path);
+    let versions_dir = paths.versions_dir();
+    if !versions_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut versions = Vec::new();
+    for entry in
+        fs::read_dir(&versions_dir).map_err(|e| format!("Failed to read versions: {}", e))?
+    {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let id = entry.file_name().to_string_lossy().to_string();
+        if let Ok(version) = read_version(&paths, &id) {
+            versions.push(version);
+        }
+    }
+
+    versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+    Ok(versions)
+}
+
+/// Media used on the timelines of saved versions
+pub fn referenced_media(project_path: &Path) -> Result<HashSet<PathBuf>, String> {
+    let paths = ProjectPaths::new(project_path);
+    let key = relative_key(project_path, &paths.editor_state_file());
+
+    let mut media = HashSet::new();
+    for version in list_versions(project_path)? {
+        let snapshot = version_snapshot(&paths, &version.id)?;
+        for clip in parse_editor_state(&paths, &snapshot, &key)? {
+            media.insert(PathBuf::from(clip.video_path));
+        }
+    }
+    Ok(media)
+}
+
+/// Rewrite the copy of `file` in every saved version, e.g. after the project moved
+pub fn rewrite_versioned_file(
+    project_path: &Path,
+    file: &Path,
+    rewrite: impl Fn(&[u8]) -> Result<Vec<u8>, String>,
+) -> Result<(), String> {
+    let paths = ProjectPaths::new(project_path);
+    let key = relative_key(project_path, file);
+
+    for version in list_versions(project_path)? {
+        let copy = key_to_path(&paths.version_files_dir(&version.id), &key);
+        if !copy.exists() {
+            continue;
+        }
+        let content =
+            fs::read(&copy).map_err(|e| format!("Failed to read {}: {}", copy.display(), e))?;
+        write_atomic(&copy, rewrite(&content)?)?;
+    }
+    Ok(())
+}
+
+pub fn delete_version(project_path: &Path, version_id: &str) -> Result<(), String> {
+    let paths = ProjectPaths::new(project_path);
+    read_version(&paths, version_id)?;
+    fs::remove_dir_all(paths.version_dir(version_id))
+        .map_err(|e| format!("Failed to delete version: {}", e))
+}
+
+/// Replace the editor state and storyboard with a saved version.
+///
+/// The restore is recorded in the undo history, so it can itself be undone.
+pub fn restore_version(project_path: &Path, version_id: &str) -> Result<(), String> {
+    let _lock = lock_project(project_path)?;
+    let paths = ProjectPaths::new(project_path);
+    let version = read_version(&paths, version_id)?;
+    let snapshot = version_snapshot(&paths, version_id)?;
+
+    // Files only in the current project are removed, so track them too
+    let mut files: BTreeSet<PathBuf> = versioned_files(&paths)?.into_iter().collect();
+    files.insert(paths.editor_state_file());
+    files.extend(snapshot.keys().map(|key| key_to_path(project_path, key)));
+    let files: Vec<PathBuf> = files.into_iter().collect();
+
+    history::record(
+        project_path,
+        &format!("Restore version \"{}\"", version.name),
+        &files,
+        || {
+            for file in &files {
+                if !snapshot.contains_key(&relative_key(project_path, file)) && file.exists() {
+                    fs::remove_file(file)
+                        .map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+                }
+            }
+            write_snapshot(project_path, &snapshot)
+        },
+    )
+}
+
+fn write_snapshot(root: &Path, snapshot: &Snapshot) -> Result<(), String> {
+    for (key, content) in snapshot {
+        let target = key_to_path(root, key);
+        if let Some(parent) = target.parent() {
+            ensure_dir(parent)?;
+        }
+        write_with_backup(&target, content)?;
+    }
+    Ok(())
+}
+
+/// Hard-link every file under `source` into `target`, copying when linking fails
+/// (for example across volumes)
+fn link_tree(source: &Path, target: &Path) -> Result<(), String> {
+    if !source.exists() {
+        return Ok(());
+    }
+
+    let mut files = Vec::new();
+    collect_files(source, &mut files)?;
+    for file in files {
+        let dest = key_to_path(target, &relative_key(source, &file));
+        if let Some(parent) = dest.parent() {
+            ensure_dir(parent)?;
+        }
+        if fs::hard_link(&file, &dest).is_err() {
+            fs::copy(&file, &dest)
+                .map_err(|e| format!("Failed to copy {}: {}", file.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Create a new project at `new_project_path` from a saved version.
+///
+/// Metadata and media come from the source project; media files are
+/// hard-linked rather than duplicated.
+pub fn fork_version(
+    project_path: &Path,
+    version_id: &str,
+    new_project_path: &Path,
+) -> Result<(), String> {
+    if new_project_path.exists() {
+        return Err(format!(
+            "Project '{}' already exists",
+            new_project_path.display()
+        ));
+    }
+
+    let paths = ProjectPaths::new(project_path);
+    let snapshot = version_snapshot(&paths, version_id)?;
+
+    // Don't leave a half-built project behind to block a retry
+    let forked = build_fork(&paths, &snapshot, new_project_path);
+    if forked.is_err() {
+        let _ = fs::remove_dir_all(new_project_path);
+    }
+    forked
+}
+
+fn build_fork(
+    paths: &ProjectPaths,
+    snapshot: &Snapshot,
+    new_project_path: &Path,
+) -> Result<(), String> {
+    let project_path = paths.root();
+    let new_paths = ProjectPaths::new(new_project_path);
+
+    ensure_dir(&new_paths.metadata_dir())?;
+    link_tree(&paths.videos_dir(), &new_paths.videos_dir())?;
+    link_tree(&paths.images_dir(), &new_paths.images_dir())?;
+    write_snapshot(new_project_path, snapshot)?;
+
+    // Media is stored relative to the project, which resolves the same in the
+    // fork; older versions may still hold absolute paths into the source
+    if let Some(json) = snapshot.get(&relative_key(project_path, &paths.editor_state_file())) {
+        let state: EditorState =
+            parse_upgraded(&EDITOR_STATE, project_path, &String::from_utf8_lossy(json))
+                .map_err(|e| format!("Failed to parse editor state: {}", e))?;
+        write_atomic(
+            &new_paths.editor_state_file(),
+            editor_state_json(project_path, &state)?,
+        )?;
+    }
+
+    // Stored form, with paths inside the project still relative
+    let mut meta = if paths.metadata_file().exists() {
+        load_json(project_path, &paths.metadata_file(), &PROJECT_META)?
+    } else {
+        ProjectMeta::new(String::new(), 0)
+    };
+    meta.created_at = current_timestamp();
+    write_project_meta(new_project_path, &meta)
+}
+
+/// Clips of a snapshot's editor state, with their media paths resolved
+fn parse_editor_state(
+    paths: &ProjectPaths,
+    snapshot: &Snapshot,
+    key: &str,
+) -> Result<Vec<TimelineClip>, String> {
+    let Some(json) = snapshot.get(key) else {
+        return Ok(Vec::new());
+    };
+    let mut state: EditorState =
+        parse_upgraded(&EDITOR_STATE, paths.root(), &String::from_utf8_lossy(json))
+            .map_err(|e| format!("Failed to parse editor state: {}", e))?;
+    for clip in &mut state.clips {
+        clip.video_path = paths.resolve(&clip.video_path);
+    }
+    Ok(state.clips)
+}
+
+fn parse_scene_index(
+    paths: &ProjectPaths,
+    snapshot: &Snapshot,
+    key: &str,
+) -> Result<Vec<SceneSummary>, String> {
+    match snapshot.get(key) {
+        Some(json) => parse_upgraded::<SceneIndexFile>(
+            &SCENE_INDEX,
+            paths.root(),
+            &String::from_utf8_lossy(json),
+        )
+        .map(|index| index.scenes)
+        .map_err(|e| format!("Failed to parse scene index: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn versioned_clip(clip: &TimelineClip) -> VersionedClip {
+    VersionedClip {
+        id: clip.id.clone(),
+        name: clip.name.clone(),
+        video_path: clip.video_path.clone(),
+    }
+}
+
+/// Compare two versions; either id may be `"current"` for the project's current files
+pub fn diff_versions(
+    project_path: &Path,
+    from_id: &str,
+    to_id: &str,
+) -> Result<VersionDiff, String> {
+    let paths = ProjectPaths::new(project_path);
+    let from = load_snapshot(&paths, from_id)?;
+    let to = load_snapshot(&paths, to_id)?;
+
+    let editor_key = relative_key(project_path, &paths.editor_state_file());
+    let index_key = relative_key(project_path, &paths.scenes_index());
+    let context_key = relative_key(project_path, &paths.context_file());
+
+    // Clips are matched by id
+    let from_clips = parse_editor_state(&paths, &from, &editor_key)?;
+    let to_clips = parse_editor_state(&paths, &to, &editor_key)?;
+
+    let clips_added = to_clips
+        .iter()
+        .filter(|clip| !from_clips.iter().any(|c| c.id == clip.id))
+        .map(versioned_clip)
+        .collect();
+    let clips_removed = from_clips
+        .iter()
+        .filter(|clip| !to_clips.iter().any(|c| c.id == clip.id))
+        .map(versioned_clip)
+        .collect();
+    let clips_retimed = to_clips
+        .iter()
+        .filter_map(|after| {
+            let before = from_clips.iter().find(|c| c.id == after.id)?;
+            let changed = before.position != after.position
+                || before.trim_start != after.trim_start
+                || before.trim_end != after.trim_end
+                || before.transition_type != after.transition_type
+                || before.transition_duration != after.transition_duration;
+            changed.then(|| ClipRetime {
+                clip: versioned_clip(after),
+                position: (before.position, after.position),
+                trim_start: (before.trim_start, after.trim_start),
+                trim_end: (before.trim_end, after.trim_end),
+                transition_changed: before.transition_type != after.transition_type
+                    || before.transition_duration != after.transition_duration,
+            })
+        })
+        .collect();
+
+    let from_scenes = parse_scene_index(&paths, &from, &index_key)?;
+    let to_scenes = parse_scene_index(&paths, &to, &index_key)?;
+    let scene_key = |id: &str| relative_key(project_path, &paths.scene_file(id));
+    let description = |snapshot: &Snapshot, id: &str| {
+        snapshot
+            .get(&scene_key(id))
+            .and_then(|content| parse_scene_markdown(&String::from_utf8_lossy(content)).ok())
+            .map(|(_, _, description)| description)
+    };
+
+    let scenes_added = to_scenes
+        .iter()
+        .filter(|scene| !from_scenes.iter().any(|s| s.id == scene.id))
+        .cloned()
+        .collect();
+    let scenes_removed = from_scenes
+        .iter()
+        .filter(|scene| !to_scenes.iter().any(|s| s.id == scene.id))
+        .cloned()
+        .collect();
+    let scenes_changed = to_scenes
+        .iter()
+        .filter_map(|after| {
+            let before = from_scenes.iter().find(|s| s.id == after.id)?;
+            let mut fields = Vec::new();
+            if before.title != after.title {
+                fields.push("title".to_string());
+            }
+            if before.duration != after.duration {
+                fields.push("duration".to_string());
+            }
+            if before.order != after.order {
+                fields.push("order".to_string());
+            }
+            if description(&from, &after.id) != description(&to, &after.id) {
+                fields.push("description".to_string());
+            }
+            (!fields.is_empty()).then(|| SceneChange {
+                id: after.id.clone(),
+                title: after.title.clone(),
+                fields,
+            })
+        })
+        .collect();
+
+    Ok(VersionDiff {
+        clips_added,
+        clips_removed,
+        clips_retimed,
+        scenes_added,
+        scenes_removed,
+        scenes_changed,
+        context_changed: from.get(&context_key) != to.get(&context_key),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::{clip_json, TempProject};
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn write_scenes(project: &TempProject, scenes: &[(&str, &str, i32, &str)]) {
+        let summaries: Vec<Value> = scenes
+            .iter()
+            .map(|(id, title, order, _)| {
+                json!({
+                    "id": id,
+                    "title": title,
+                    "duration": "4s",
+                    "hasReferenceImage": false,
+                    "order": order,
+                })
+            })
+            .collect();
+        let index = json!({ "schemaVersion": SCENE_INDEX.current(), "scenes": summaries });
+        project.write(project.scenes_index(), index.to_string());
+
+        for (id, title, _, description) in scenes {
+            let markdown = format!("# {}\n\nDuration: 4s\n\n{}\n", title, description);
+            project.write(project.scene_file(id), markdown);
+        }
+    }
+
+    fn clip(id: &str, position: f64, trim_start: f64, trim_end: f64) -> Value {
+        clip_json(
+            id,
+            &format!("videos/{}.mp4", id),
+            position,
+            trim_start,
+            trim_end,
+        )
+    }
+
+    fn ids(clips: &[VersionedClip]) -> Vec<&str> {
+        clips.iter().map(|clip| clip.id.as_str()).collect()
+    }
+
+    #[test]
+    fn diffs_a_saved_version_against_the_current_files() {
+        let project = TempProject::new();
+        let root = project.root();
+        project.write_clips(vec![clip("a", 0.0, 0.0, 4.0), clip("b", 4.0, 0.0, 4.0)]);
+        write_scenes(
+            &project,
+            &[
+                ("s1", "Intro", 0, "A quiet street"),
+                ("s2", "Chase", 1, "Cars"),
+                ("s3", "Outro", 2, "Credits"),
+            ],
+        );
+        project.write(project.context_file(), "Noir");
+        let version = save_version(root, "Before", None).unwrap();
+
+        project.write_clips(vec![clip("b", 0.0, 1.0, 4.0), clip("c", 3.0, 0.0, 8.0)]);
+        write_scenes(
+            &project,
+            &[
+                ("s1", "Intro", 0, "A busy street"),
+                ("s2", "The chase", 2, "Cars"),
+                ("s4", "Epilogue", 1, "Sunrise"),
+            ],
+        );
+        project.write(project.context_file(), "Comedy");
+
+        let diff = diff_versions(root, &version.id, CURRENT_VERSION).unwrap();
+
+        assert_eq!(ids(&diff.clips_added), ["c"]);
+        assert_eq!(ids(&diff.clips_removed), ["a"]);
+        assert_eq!(diff.clips_retimed.len(), 1);
+        let retime = &diff.clips_retimed[0];
+        assert_eq!(retime.clip.id, "b");
+        assert_eq!(retime.position, (4.0, 0.0));
+        assert_eq!(retime.trim_start, (0.0, 1.0));
+        assert!(!retime.transition_changed);
+        // Media paths are reported resolved against the project
+        assert_eq!(
+            diff.clips_added[0].video_path,
+            project.resolve("videos/c.mp4")
+        );
+
+        let scene_ids = |scenes: &[SceneSummary]| -> Vec<String> {
+            scenes.iter().map(|scene| scene.id.clone()).collect()
+        };
+        assert_eq!(scene_ids(&diff.scenes_added), ["s4"]);
+        assert_eq!(scene_ids(&diff.scenes_removed), ["s3"]);
+        let changed: Vec<(&str, Vec<&str>)> = diff
+            .scenes_changed
+            .iter()
+            .map(|change| {
+                let fields = change.fields.iter().map(String::as_str).collect();
+                (change.id.as_str(), fields)
+            })
+            .collect();
+        assert_eq!(
+            changed,
+            [("s1", vec!["description"]), ("s2", vec!["title", "order"])]
+        );
+        assert!(diff.context_changed);
+    }
+
+    #[test]
+    fn unchanged_files_have_an_empty_diff() {
+        let project = TempProject::new();
+        let root = project.root();
+        project.write_clips(vec![clip("a", 0.0, 0.0, 4.0)]);
+        write_scenes(&project, &[("s1", "Intro", 0, "A quiet street")]);
+        let version = save_version(root, "Saved", None).unwrap();
+
+        let diff = diff_versions(root, &version.id, CURRENT_VERSION).unwrap();
+
+        assert!(diff.clips_added.is_empty() && diff.clips_removed.is_empty());
+        assert!(diff.clips_retimed.is_empty());
+        assert!(diff.scenes_added.is_empty() && diff.scenes_removed.is_empty());
+        assert!(diff.scenes_changed.is_empty());
+        assert!(!diff.context_changed);
+    }
+
+    #[test]
+    fn restoring_a_version_brings_back_its_editor_state() {
+        let project = TempProject::new();
+        let root = project.root();
+        project.write_clips(vec![clip("a", 0.0, 0.0, 4.0)]);
+        let version = save_version(root, "Saved", None).unwrap();
+        project.write_clips(vec![clip("b", 0.0, 0.0, 8.0)]);
+
+        restore_version(root, &version.id).unwrap();
+
+        let diff = diff_versions(root, &version.id, CURRENT_VERSION).unwrap();
+        assert!(diff.clips_added.is_empty() && diff.clips_removed.is_empty());
+        assert!(project.editor_state_file().exists());
+    }
+
+    #[test]
+    fn diffing_a_missing_version_fails() {
+        let project = TempProject::new();
+        let missing = Uuid::new_v4().to_string();
+        let diff = diff_versions(project.root(), &missing, CURRENT_VERSION);
+        assert!(diff.is_err());
+    }
+}
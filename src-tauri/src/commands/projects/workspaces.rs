@@ -0,0 +1,201 @@
+//! Workspaces: folders that hold projects
+//!
+//! `~/sixsevenstudio` is always a workspace. Others, such as a folder on an
+//! external drive or a shared volume, are registered in the app settings store
+//! along with which workspace is active. New projects go into the active
+//! workspace and project names are looked up there.
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::FsExt;
+use tauri_plugin_store::StoreExt;
+
+use super::filesystem::ensure_dir;
+use super::paths::ProjectPaths;
+use super::types::{Workspace, WorkspaceRegistry, WorkspaceSummary};
+
+pub(crate) const SETTINGS_STORE: &str = "settings.json";
+const WORKSPACES_KEY: &str = "workspaces";
+const DEFAULT_WORKSPACE_NAME: &str = "Default";
+
+fn load_registry(app: &AppHandle) -> Result<WorkspaceRegistry, String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    match store.get(WORKSPACES_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| format!("Failed to read workspace settings: {}", e)),
+        None => Ok(WorkspaceRegistry::default()),
+    }
+}
+
+fn save_registry(app: &AppHandle, registry: &WorkspaceRegistry) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(registry)
+        .map_err(|e| format!("Failed to serialize workspace settings: {}", e))?;
+    store.set(WORKSPACES_KEY, value);
+    store.save().map_err(|e| e.to_string())
+}
+
+fn active_path(registry: &WorkspaceRegistry) -> PathBuf {
+    registry
+        .active
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(ProjectPaths::default_workspace_dir)
+}
+
+fn summary(workspace: Workspace, active: &Path) -> WorkspaceSummary {
+    let path = Path::new(&workspace.path);
+    let is_default = path == ProjectPaths::default_workspace_dir();
+    WorkspaceSummary {
+        active: path == active,
+        is_default,
+        // The default workspace is created when first used
+        available: is_default || path.is_dir(),
+        name: workspace.name,
+        path: workspace.path,
+    }
+}
+
+/// All workspaces, the default one first
+pub fn list_workspaces(app: &AppHandle) -> Result<Vec<WorkspaceSummary>, String> {
+    let registry = load_registry(app)?;
+    let active = active_path(&registry);
+
+    let default = Workspace {
+        name: DEFAULT_WORKSPACE_NAME.to_string(),
+        path: ProjectPaths::default_workspace_dir()
+            .to_string_lossy()
+            .to_string(),
+    };
+    Ok(std::iter::once(default)
+        .chain(registry.workspaces)
+        .map(|workspace| summary(workspace, &active))
+        .collect())
+}
+
+/// Folder of the active workspace
+pub fn active_workspace(app: &AppHandle) -> Result<PathBuf, String> {
+    let workspace = active_path(&load_registry(app)?);
+    if workspace == ProjectPaths::default_workspace_dir() {
+        ensure_dir(&workspace)?;
+    } else if !workspace.is_dir() {
+        // Don't recreate the folder of an unplugged drive on the system disk
+        return Err(format!(
+            "Workspace {} is not available; reconnect it or switch to another workspace",
+            workspace.display()
+        ));
+    }
+    Ok(workspace)
+}
+
+/// Let the webview load media from `path`
+fn allow_workspace(app: &AppHandle, path: &Path) -> Result<(), String> {
+    app.asset_protocol_scope()
+        .allow_directory(path, true)
+        .and_then(|_| app.fs_scope().allow_directory(path, true))
+        .map_err(|e| format!("Failed to allow access to {}: {}", path.display(), e))
+}
+
+/// Extend the webview's file access to registered workspaces; the default
+/// workspace is covered by the static configuration
+pub fn allow_registered_workspaces(app: &AppHandle) -> Result<(), String> {
+    for workspace in load_registry(app)?.workspaces {
+        let path = Path::new(&workspace.path);
+        if path.is_dir() {
+            allow_workspace(app, path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Register `path` as a workspace, creating the folder if needed
+pub fn add_workspace(
+    app: &AppHandle,
+    path: &Path,
+    name: Option<&str>,
+) -> Result<WorkspaceSummary, String> {
+    if !path.is_absolute() {
+        return Err(format!(
+            "Workspace path must be absolute: {}",
+            path.display()
+        ));
+    }
+
+    let mut registry = load_registry(app)?;
+    let registered = path == ProjectPaths::default_workspace_dir()
+        || registry
+            .workspaces
+            .iter()
+            .any(|w| Path::new(&w.path) == path);
+    if registered {
+        return Err(format!("{} is already a workspace", path.display()));
+    }
+
+    ensure_dir(path)?;
+    allow_workspace(app, path)?;
+
+    let name = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None => path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string()),
+    };
+    let workspace = Workspace {
+        name,
+        path: path.to_string_lossy().to_string(),
+    };
+    registry.workspaces.push(workspace.clone());
+    save_registry(app, &registry)?;
+
+    Ok(summary(workspace, &active_path(&registry)))
+}
+
+/// Unregister a workspace. Its projects stay on disk.
+pub fn remove_workspace(app: &AppHandle, path: &Path) -> Result<(), String> {
+    if path == ProjectPaths::default_workspace_dir() {
+        return Err("The default workspace cannot be removed".to_string());
+    }
+
+    let mut registry = load_registry(app)?;
+    let count = registry.workspaces.len();
+    registry.workspaces.retain(|w| Path::new(&w.path) != path);
+    if registry.workspaces.len() == count {
+        return Err(format!("{} is not a workspace", path.display()));
+    }
+
+    if active_path(&registry) == path {
+        registry.active = None;
+    }
+    save_registry(app, &registry)
+}
+
+/// Make `path` the active workspace
+pub fn switch_workspace(app: &AppHandle, path: &Path) -> Result<WorkspaceSummary, String> {
+    let mut registry = load_registry(app)?;
+
+    let workspace = if path == ProjectPaths::default_workspace_dir() {
+        registry.active = None;
+        Workspace {
+            name: DEFAULT_WORKSPACE_NAME.to_string(),
+            path: path.to_string_lossy().to_string(),
+        }
+    } else {
+        let workspace = registry
+            .workspaces
+            .iter()
+            .find(|w| Path::new(&w.path) == path)
+            .cloned()
+            .ok_or_else(|| format!("{} is not a workspace", path.display()))?;
+        if !path.is_dir() {
+            return Err(format!("Workspace {} is not available", path.display()));
+        }
+        // The drive may have been connected after startup
+        allow_workspace(app, path)?;
+        registry.active = Some(workspace.path.clone());
+        workspace
+    };
+
+    save_registry(app, &registry)?;
+    Ok(summary(workspace, path))
+}
@@ -0,0 +1,146 @@
+//! Build timelines from the storyboard
+//!
+//! Storyboard scenes are numbered by their position in `list_scenes` (the
+//! first scene is scene 1), matching `VideoMeta::scene_number`.
+
+use crate::commands::projects::paths::ProjectPaths;
+use crate::commands::projects::types::{ProjectMeta, SceneSummary, VideoMeta};
+use crate::commands::video_editor::timeline::{new_clip_id, recompute_positions};
+use crate::commands::video_editor::types::{MissingScene, TimelineClip};
+
+/// Transition placed between assembled scenes unless the caller picks another
+pub const DEFAULT_TRANSITION_TYPE: &str = "fade";
+pub const DEFAULT_TRANSITION_DURATION: f64 = 1.0;
+
+/// How to pick and lay out a video for each scene
+pub struct AssemblyOptions {
+    /// Use the most recent take when a scene has no selected take
+    pub fallback_to_latest: bool,
+    /// Trim clips to the scene's declared duration
+    pub trim_to_scene: bool,
+    /// Transition leading out of every clip but the last
+    pub transition: Option<(String, f64)>,
+}
+
+/// Parse a storyboard duration such as `"8s"` into seconds
+fn parse_scene_duration(duration: &str) -> Option<f64> {
+    duration
+        .trim()
+        .trim_end_matches('s')
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0)
+}
+
+/// A full-length timeline clip for a generated video, named like the editor's library
+fn clip_for_video(paths: &ProjectPaths, video: &VideoMeta, scene: &SceneSummary) -> TimelineClip {
+    let scene_number = video.scene_number.unwrap_or_default();
+    let title = video.scene_title.as_deref().unwrap_or(&scene.title);
+    let duration = video.duration as f64;
+
+    TimelineClip {
+        id: new_clip_id(),
+        name: format!("Scene {}: {}", scene_number, title),
+        video_path: paths
+            .video_file(&format!("{}.mp4", video.id))
+            .to_string_lossy()
+            .to_string(),
+        thumbnail: None,
+        original_duration: duration,
+        created_at: video.created_at,
+        position: 0.0,
+        trim_start: 0.0,
+        trim_end: duration,
+        duration,
+        transition_type: None,
+        transition_duration: None,
+    }
+}
+
+/// The take to use for a scene: its selected take, or with `fallback_to_latest`
+/// the most recently generated one. Only downloaded videos are considered.
+fn pick_take<'a>(
+    paths: &ProjectPaths,
+    meta: &'a ProjectMeta,
+    scene_number: i32,
+    fallback_to_latest: bool,
+) -> Option<&'a VideoMeta> {
+    let downloaded = |video: &&VideoMeta| paths.video_file(&format!("{}.mp4", video.id)).exists();
+
+    let selected = meta
+        .selected_takes
+        .get(&scene_number)
+        .and_then(|id| meta.videos.iter().find(|v| &v.id == id))
+        .filter(downloaded);
+
+    if selected.is_some() || !fallback_to_latest {
+        return selected;
+    }
+
+    meta.videos
+        .iter()
+        .filter(|v| v.scene_number == Some(scene_number))
+        .filter(downloaded)
+        .max_by_key(|v| v.created_at)
+}
+
+/// Clips for each storyboard scene in order, plus the scenes no video could be found for
+pub fn assemble_storyboard(
+    paths: &ProjectPaths,
+    meta: &ProjectMeta,
+    scenes: &[SceneSummary],
+    options: &AssemblyOptions,
+) -> (Vec<TimelineClip>, Vec<MissingScene>) {
+    let mut clips = Vec::new();
+    let mut missing = Vec::new();
+
+    for (index, scene) in scenes.iter().enumerate() {
+        let scene_number = index as i32 + 1;
+        let Some(video) = pick_take(paths, meta, scene_number, options.fallback_to_latest) else {
+            missing.push(MissingScene {
+                scene_number,
+                scene_id: scene.id.clone(),
+                title: scene.title.clone(),
+            });
+            continue;
+        };
+
+        let mut clip = clip_for_video(paths, video, scene);
+        if options.trim_to_scene {
+            if let Some(seconds) = parse_scene_duration(&scene.duration) {
+                clip.trim_end = seconds.min(clip.original_duration);
+                clip.duration = clip.trim_end - clip.trim_start;
+            }
+        }
+        clips.push(clip);
+    }
+
+    // Transitions lead out of a clip, so the last one gets none
+    if let Some((transition_type, transition_duration)) = &options.transition {
+        let last_index = clips.len().saturating_sub(1);
+        for clip in clips.iter_mut().take(last_index) {
+            clip.transition_type = Some(transition_type.clone());
+            clip.transition_duration = Some(transition_duration.min(clip.duration / 2.0));
+        }
+    }
+
+    recompute_positions(&mut clips);
+    (clips, missing)
+}
+
+/// Clips for the selected take of each scene, in storyboard order.
+///
+/// Scenes without a selected take, or whose take has not been downloaded yet, are left out.
+pub fn rough_cut_clips(
+    paths: &ProjectPaths,
+    meta: &ProjectMeta,
+    scenes: &[SceneSummary],
+) -> Vec<TimelineClip> {
+    let options = AssemblyOptions {
+        fallback_to_latest: false,
+        trim_to_scene: false,
+        transition: None,
+    };
+    assemble_storyboard(paths, meta, scenes, &options).0
+}
@@ -1,14 +1,108 @@
-use std::fs;
-use tauri::AppHandle;
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_dialog::DialogExt;
 use uuid::Uuid;
 
 use crate::commands::video_editor::{
-    ffmpeg::{concatenate_fast, concatenate_with_transitions, verify_ffmpeg_available, generate_waveform_image, generate_sprite_image, get_video_duration},
-    types::{EditorState, TimelineClip},
+    assembly::{assemble_storyboard, rough_cut_clips, AssemblyOptions, DEFAULT_TRANSITION_DURATION, DEFAULT_TRANSITION_TYPE},
+    ffmpeg::{concatenate_fast, concatenate_with_transitions, verify_ffmpeg_available, generate_waveform_image, generate_waveform_peaks, generate_sprite_image, generate_proxy, probe_video, export_format, remux, EXPORT_FORMATS, detect_scene_changes, detect_dead_air, dead_air_trim, DEFAULT_SCENE_THRESHOLD},
+    import::{collect_folder_videos, import_file, parse_import_mode, ImportContext, ImportOutcome, VIDEO_EXTENSIONS},
+    timeline::{recompute_positions, split_clip_at},
+    types::{ClipDeadAirReport, EditorState, ImportFailure, ImportSkip, ImportSummary, SceneDetectionResult, StoryboardAssembly, TimelineClip, WaveformPeaks},
 };
 
+use crate::commands::projects::filesystem::{
+    collect_files, editor_state_json, file_fingerprint, hash_file, lock_project,
+    read_editor_state, read_project_meta, update_project_meta, write_atomic, write_with_backup,
+};
+use crate::commands::projects::history;
+use crate::commands::projects::migrations::{load_json, EDITOR_STATE};
+use crate::commands::projects::library::normalize_tags;
 use crate::commands::projects::paths::ProjectPaths;
+use crate::commands::projects::recycle;
+use crate::commands::projects::repair;
+use crate::commands::projects::scenes;
+use crate::commands::projects::types::{ImportedVideoMeta, MissingMedia, ProjectMeta, RecycledRecord};
+
+/// Shortest clip auto-trim is allowed to leave behind, in seconds
+const MIN_AUTO_TRIM_DURATION: f64 = 0.5;
+
+/// Files processed at once by batch imports, unless the caller asks otherwise
+const DEFAULT_IMPORT_CONCURRENCY: usize = 3;
+const MAX_IMPORT_CONCURRENCY: usize = 8;
+
+/// Path to read a source video from while editing: its proxy when the
+/// project uses proxies and one has been generated, otherwise the original.
+fn editing_source(paths: &ProjectPaths, meta: &ProjectMeta, video_path: &str) -> String {
+    if meta.use_proxies {
+        if let Some(key) = meta.proxy_key(Path::new(video_path)) {
+            let proxy = paths.proxy_file(&key);
+            if proxy.exists() {
+                return proxy.to_string_lossy().to_string();
+            }
+        }
+    }
+    video_path.to_string()
+}
+
+/// Render timeline clips into a single video, re-encoding only when transitions need it
+async fn render_timeline(
+    app: &AppHandle,
+    clips: &[TimelineClip],
+    output_path: &Path,
+    temp_dir: &Path,
+) -> Result<(), String> {
+    // Check if any clips have transitions configured
+    let has_transitions = clips.iter().any(|clip| clip.transition_type.is_some());
+
+    // Choose the appropriate concatenation method
+    let result = if has_transitions {
+        // Use transition-aware concatenation (requires re-encoding)
+        concatenate_with_transitions(app, clips, output_path, temp_dir).await
+    } else {
+        // Use fast codec copy (no re-encoding)
+        concatenate_fast(app, clips, output_path, temp_dir).await
+    };
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(output_path);
+    }
+    result
+}
+
+/// Generate missing proxies for `sources` in the background, one at a time.
+///
+/// Each source path comes with its proxy key (see `ProjectMeta::proxy_key`).
+/// Emits `proxy-generated` with the source and proxy paths as each one finishes.
+fn spawn_proxy_generation(app: AppHandle, project_root: PathBuf, sources: Vec<(String, String)>) {
+    tauri::async_runtime::spawn(async move {
+        let paths = ProjectPaths::new(project_root);
+        if let Err(e) = std::fs::create_dir_all(paths.proxies_dir()) {
+            eprintln!("Failed to create proxies cache directory: {}", e);
+            return;
+        }
+
+        for (source, key) in sources {
+            let proxy_path = paths.proxy_file(&key);
+
+            match generate_proxy(&app, &source, &proxy_path).await {
+                Ok(true) => {
+                    let _ = app.emit(
+                        "proxy-generated",
+                        serde_json::json!({
+                            "videoPath": source,
+                            "proxyPath": proxy_path.to_string_lossy(),
+                        }),
+                    );
+                }
+                Ok(false) => {}
+                Err(e) => eprintln!("Failed to generate proxy for {}: {}", source, e),
+            }
+        }
+    });
+}
 
 /// Create a stitched preview video from timeline clips
 #[tauri::command]
@@ -26,7 +120,7 @@ pub async fn create_preview_video(
     let paths = ProjectPaths::from_name(&app, &project_name)?;
 
     // Create temp directory for intermediate files
-    let temp_dir = paths.root().join("temp");
+    let temp_dir = paths.temp_dir();
     std::fs::create_dir_all(&temp_dir)
         .map_err(|e| format!("Failed to create temp directory: {}", e))?;
 
@@ -38,28 +132,43 @@ pub async fn create_preview_video(
         .ok_or("Invalid output path")?
         .to_string();
 
-    // Check if any clips have transitions configured
-    let has_transitions = clips.iter().any(|clip| clip.transition_type.is_some());
+    // Preview from proxies when the project has them enabled
+    let meta = read_project_meta(paths.root())?;
+    let clips: Vec<TimelineClip> = clips
+        .into_iter()
+        .map(|clip| TimelineClip {
+            video_path: editing_source(&paths, &meta, &clip.video_path),
+            ..clip
+        })
+        .collect();
 
-    // Choose the appropriate concatenation method
-    let result = if has_transitions {
-        // Use transition-aware concatenation (requires re-encoding)
-        concatenate_with_transitions(&app, &clips, &output_path, &temp_dir).await
-    } else {
-        // Use fast codec copy (no re-encoding)
-        concatenate_fast(&app, &clips, &output_path, &temp_dir).await
-    };
+    render_timeline(&app, &clips, &output_path, &temp_dir).await?;
 
-    match result {
-        Ok(_) => Ok(output_path_str),
-        Err(e) => {
-            let _ = std::fs::remove_file(&output_path);
-            Err(e)
-        }
+    Ok(output_path_str)
+}
+
+/// Whether `state` changes the saved clips, as opposed to only the selection
+/// or preview
+fn timeline_changed(paths: &ProjectPaths, state: &EditorState) -> Result<bool, String> {
+    let editor_state_file = paths.editor_state_file();
+    if !editor_state_file.exists() {
+        return Ok(true);
+    }
+
+    // Compare stored forms so paths match however the frontend spells them
+    let saved: EditorState = load_json(paths.root(), &editor_state_file, &EDITOR_STATE)?;
+    let mut clips = state.clips.clone();
+    for clip in &mut clips {
+        clip.video_path = paths.relativize(&clip.video_path);
     }
+    let as_value = |clips: &[TimelineClip]| serde_json::to_value(clips).map_err(|e| e.to_string());
+    Ok(as_value(&saved.clips)? != as_value(&clips)?)
 }
 
 /// Save the video editor state to disk
+///
+/// Only changes to the clips are recorded in the undo history; selecting a
+/// clip or rendering a new preview is saved without an entry.
 #[tauri::command]
 pub async fn save_editor_state(
     app: AppHandle,
@@ -68,14 +177,18 @@ pub async fn save_editor_state(
 ) -> Result<(), String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
     let editor_state_file = paths.editor_state_file();
+    let json = editor_state_json(paths.root(), &state)?;
 
-    let json = serde_json::to_string_pretty(&state)
-        .map_err(|e| format!("Failed to serialize editor state: {}", e))?;
-
-    std::fs::write(&editor_state_file, json)
-        .map_err(|e| format!("Failed to write editor state: {}", e))?;
-
-    Ok(())
+    let _lock = lock_project(paths.root())?;
+    if !timeline_changed(&paths, &state)? {
+        return write_with_backup(&editor_state_file, json);
+    }
+    history::record(
+        paths.root(),
+        "Edit timeline",
+        std::slice::from_ref(&editor_state_file),
+        || write_with_backup(&editor_state_file, json),
+    )
 }
 
 /// Load the video editor state from disk
@@ -85,36 +198,142 @@ pub async fn load_editor_state(
     project_name: String,
 ) -> Result<Option<EditorState>, String> {
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    let editor_state_file = paths.editor_state_file();
+    read_editor_state(paths.root())
+}
 
-    if !editor_state_file.exists() {
-        return Ok(None);
+/// Assemble a rough cut from the selected take of each storyboard scene
+///
+/// With `apply`, the rough cut replaces the saved editor state.
+#[tauri::command]
+pub async fn assemble_rough_cut(
+    app: AppHandle,
+    project_name: String,
+    apply: Option<bool>,
+) -> Result<EditorState, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    let scenes = scenes::list_scenes(paths.root())?;
+
+    let clips = rough_cut_clips(&paths, &meta, &scenes);
+    let state = EditorState {
+        schema_version: EDITOR_STATE.current(),
+        selected_clip_id: None,
+        preview_video_path: None,
+        clips,
+    };
+
+    if apply.unwrap_or(false) {
+        save_editor_state(app.clone(), project_name.clone(), state.clone()).await?;
     }
 
-    let json = std::fs::read_to_string(&editor_state_file)
-        .map_err(|e| format!("Failed to read editor state: {}", e))?;
+    Ok(state)
+}
 
-    let state: EditorState =
-        serde_json::from_str(&json).map_err(|e| format!("Failed to parse editor state: {}", e))?;
+/// Build a timeline from the storyboard's scene order
+///
+/// Each scene uses its selected take, or the most recent downloaded video for
+/// that scene, trimmed to the scene's declared duration. `transition_type`
+/// defaults to the project's default transition, or a cross fade between
+/// scenes; pass `"none"` for hard cuts. With `apply`, the assembled timeline
+/// replaces the saved editor state.
+#[tauri::command]
+pub async fn assemble_timeline_from_storyboard(
+    app: AppHandle,
+    project_name: String,
+    transition_type: Option<String>,
+    transition_duration: Option<f64>,
+    apply: Option<bool>,
+) -> Result<StoryboardAssembly, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    let scenes = scenes::list_scenes(paths.root())?;
+
+    let transition_type = transition_type
+        .or_else(|| meta.defaults.transition_type.clone())
+        .unwrap_or_else(|| DEFAULT_TRANSITION_TYPE.to_string());
+    let transition = (transition_type != "none").then(|| {
+        (
+            transition_type,
+            transition_duration
+                .or(meta.defaults.transition_duration)
+                .unwrap_or(DEFAULT_TRANSITION_DURATION),
+        )
+    });
+    let options = AssemblyOptions {
+        fallback_to_latest: true,
+        trim_to_scene: true,
+        transition,
+    };
 
-    Ok(Some(state))
+    let (clips, missing_scenes) = assemble_storyboard(&paths, &meta, &scenes, &options);
+    let state = EditorState {
+        schema_version: EDITOR_STATE.current(),
+        selected_clip_id: None,
+        preview_video_path: None,
+        clips,
+    };
+
+    if apply.unwrap_or(false) {
+        save_editor_state(app.clone(), project_name.clone(), state.clone()).await?;
+    }
+
+    Ok(StoryboardAssembly {
+        state,
+        missing_scenes,
+    })
 }
 
 /// Export video by copying preview to user-selected location
+///
+/// The save dialog suggests the project's default output format; a preview
+/// saved as another supported container is remuxed into it. When the project
+/// edits with proxies, the preview is low-res, so the timeline `clips` are
+/// rendered again from the original media instead.
 #[tauri::command]
-pub async fn export_video(app: AppHandle, preview_path: String) -> Result<String, String> {
+pub async fn export_video(
+    app: AppHandle,
+    preview_path: String,
+    project_name: Option<String>,
+    clips: Option<Vec<TimelineClip>>,
+) -> Result<String, String> {
+    let project = match &project_name {
+        Some(name) => {
+            let paths = ProjectPaths::from_name(&app, name)?;
+            let meta = read_project_meta(paths.root())?;
+            Some((paths, meta))
+        }
+        None => None,
+    };
+    let output_format = project
+        .as_ref()
+        .and_then(|(_, meta)| meta.defaults.output_format.as_deref())
+        .and_then(export_format)
+        .unwrap_or("mp4");
+    let proxy_project = match (project, &clips) {
+        (Some((paths, meta)), Some(clips)) if !clips.is_empty() => {
+            meta.use_proxies.then_some(paths)
+        }
+        _ => None,
+    };
+
     // Verify preview file exists
-    if !std::path::Path::new(&preview_path).exists() {
+    if proxy_project.is_none() && !std::path::Path::new(&preview_path).exists() {
         return Err("Preview video not found. Please generate a preview first.".to_string());
     }
 
-    // Open save dialog
-    let file_path = app
-        .dialog()
-        .file()
-        .add_filter("MP4 Video", &["mp4"])
-        .add_filter("All Video Files", &["mp4", "mkv", "mov", "avi"])
-        .set_file_name("exported_video.mp4")
+    // Open save dialog, the default format's filter first
+    let extensions: Vec<&str> = EXPORT_FORMATS.iter().map(|(extension, _)| *extension).collect();
+    let mut dialog = app.dialog().file();
+    let formats = EXPORT_FORMATS
+        .iter()
+        .filter(|(extension, _)| *extension == output_format)
+        .chain(EXPORT_FORMATS.iter().filter(|(extension, _)| *extension != output_format));
+    for (extension, label) in formats {
+        dialog = dialog.add_filter(*label, &[*extension]);
+    }
+    let file_path = dialog
+        .add_filter("All Video Files", &extensions)
+        .set_file_name(format!("exported_video.{}", output_format))
         .blocking_save_file();
 
     let file_path = match file_path {
@@ -123,10 +342,33 @@ pub async fn export_video(app: AppHandle, preview_path: String) -> Result<String
     };
 
     let output_path = file_path.to_string();
+    let container = Path::new(&output_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(export_format)
+        .ok_or_else(|| {
+            format!("Unsupported export format; use one of: {}", extensions.join(", "))
+        })?;
+
+    if let (Some(paths), Some(clips)) = (proxy_project, clips) {
+        verify_ffmpeg_available(Some(&app))?;
+
+        let temp_dir = paths.temp_dir();
+        std::fs::create_dir_all(&temp_dir)
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+
+        render_timeline(&app, &clips, Path::new(&output_path), &temp_dir).await?;
+        return Ok(output_path);
+    }
 
-    // Copy preview video to selected location
-    std::fs::copy(&preview_path, &output_path)
-        .map_err(|e| format!("Failed to export video: {}", e))?;
+    // The preview is an MP4; other containers get its streams without re-encoding
+    if container == "mp4" {
+        std::fs::copy(&preview_path, &output_path)
+            .map_err(|e| format!("Failed to export video: {}", e))?;
+    } else {
+        verify_ffmpeg_available(Some(&app))?;
+        remux(&app, &preview_path, Path::new(&output_path)).await?;
+    }
 
     Ok(output_path)
 }
@@ -164,10 +406,13 @@ pub async fn generate_clip_waveform(
         .find(|c| c.id == clip_id)
         .ok_or(format!("Clip {} not found", clip_id))?;
 
+    let meta = read_project_meta(paths.root())?;
+    let source = editing_source(&paths, &meta, &clip.video_path);
+
     // Generate waveform
     generate_waveform_image(
         &app,
-        &clip.video_path,
+        &source,
         clip.trim_start,
         clip.trim_end,
         &cache_path,
@@ -182,6 +427,65 @@ pub async fn generate_clip_waveform(
     Ok(cache_path.to_str().map(|s| s.to_string()))
 }
 
+/// Generate multi-resolution waveform peaks for a clip's source video
+///
+/// Peaks are cached per source file, so clips cut from the same video share them.
+/// Returns `None` if the video has no audio track.
+#[tauri::command]
+pub async fn generate_clip_peaks(
+    app: AppHandle,
+    project_name: String,
+    clip_id: String,
+) -> Result<Option<WaveformPeaks>, String> {
+    verify_ffmpeg_available(Some(&app))?;
+
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+
+    // Ensure cache directories exist
+    std::fs::create_dir_all(paths.waveforms_dir())
+        .map_err(|e| format!("Failed to create waveforms cache directory: {}", e))?;
+
+    // Load editor state to get clip information
+    let editor_state = load_editor_state(app.clone(), project_name.clone())
+        .await
+        .map_err(|e| format!("Failed to load editor state: {}", e))?;
+
+    let state = editor_state.ok_or("Editor state not found")?;
+    let clip = state.clips.iter()
+        .find(|c| c.id == clip_id)
+        .ok_or(format!("Clip {} not found", clip_id))?;
+
+    let video_name = std::path::Path::new(&clip.video_path)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid clip video path")?
+        .to_string();
+
+    // Check cache first
+    let cache_path = paths.peaks_file(&video_name);
+    if cache_path.exists() {
+        let json = std::fs::read_to_string(&cache_path)
+            .map_err(|e| format!("Failed to read cached peaks: {}", e))?;
+        if let Ok(peaks) = serde_json::from_str::<WaveformPeaks>(&json) {
+            return Ok(Some(peaks));
+        }
+    }
+
+    let meta = read_project_meta(paths.root())?;
+    let source = editing_source(&paths, &meta, &clip.video_path);
+
+    let peaks = match generate_waveform_peaks(&app, &source).await? {
+        Some(peaks) => peaks,
+        None => return Ok(None),
+    };
+
+    let json = serde_json::to_string(&peaks)
+        .map_err(|e| format!("Failed to serialize peaks: {}", e))?;
+    write_atomic(&cache_path, json)?;
+
+    Ok(Some(peaks))
+}
+
 /// Generate sprite image for a clip
 #[tauri::command]
 pub async fn generate_clip_sprite(
@@ -215,10 +519,13 @@ pub async fn generate_clip_sprite(
         .find(|c| c.id == clip_id)
         .ok_or(format!("Clip {} not found", clip_id))?;
 
+    let meta = read_project_meta(paths.root())?;
+    let source = editing_source(&paths, &meta, &clip.video_path);
+
     // Generate sprite with width-based frame count
     generate_sprite_image(
         &app,
-        &clip.video_path,
+        &source,
         clip.trim_start,
         clip.trim_end,
         &cache_path,
@@ -229,15 +536,187 @@ pub async fn generate_clip_sprite(
     Ok(cache_path.to_str().map(|s| s.to_string()))
 }
 
+/// Detect shot changes in a timeline clip
+///
+/// `split` controls what is done with the detected cuts:
+/// - `"timeline"` replaces the clip with one clip per shot in the editor state
+/// - `"storyboard"` appends one storyboard scene per shot
+/// - anything else only reports the cuts
+#[tauri::command]
+pub async fn detect_scenes(
+    app: AppHandle,
+    project_name: String,
+    clip_id: String,
+    threshold: Option<f64>,
+    split: Option<String>,
+) -> Result<SceneDetectionResult, String> {
+    verify_ffmpeg_available(Some(&app))?;
+
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+
+    // Load editor state to get clip information
+    let editor_state = load_editor_state(app.clone(), project_name.clone())
+        .await
+        .map_err(|e| format!("Failed to load editor state: {}", e))?;
+
+    let mut state = editor_state.ok_or("Editor state not found")?;
+    let clip_index = state.clips.iter()
+        .position(|c| c.id == clip_id)
+        .ok_or(format!("Clip {} not found", clip_id))?;
+    let clip = state.clips[clip_index].clone();
+
+    let threshold = threshold.unwrap_or(DEFAULT_SCENE_THRESHOLD).clamp(0.0, 1.0);
+    let cuts = detect_scene_changes(&app, &clip.video_path, clip.trim_start, clip.trim_end, threshold).await?;
+
+    let cut_times: Vec<f64> = cuts.iter().map(|c| c.time).collect();
+    let segments = split_clip_at(&clip, &cut_times);
+
+    let mut result = SceneDetectionResult {
+        cuts,
+        clips: Vec::new(),
+        scene_ids: Vec::new(),
+    };
+
+    match split.as_deref() {
+        Some("timeline") => {
+            state.clips.splice(clip_index..=clip_index, segments.clone());
+            recompute_positions(&mut state.clips);
+            if state.selected_clip_id.as_deref() == Some(clip_id.as_str()) {
+                state.selected_clip_id = segments.first().map(|c| c.id.clone());
+            }
+            save_editor_state(app.clone(), project_name.clone(), state).await?;
+            result.clips = segments;
+        }
+        Some("storyboard") => {
+            for (index, segment) in segments.iter().enumerate() {
+                let scene_id = format!("scene-{}", Uuid::new_v4());
+                let title = format!("{} - Shot {}", clip.name, index + 1);
+                let description = format!(
+                    "Shot from {} ({:.2}s - {:.2}s)",
+                    clip.name, segment.trim_start, segment.trim_end
+                );
+                scenes::write_scene(
+                    paths.root(),
+                    &scene_id,
+                    &title,
+                    &description,
+                    nearest_scene_duration(segment.duration),
+                    None,
+                )?;
+                result.scene_ids.push(scene_id);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(result)
+}
+
+/// Storyboard scenes only support fixed durations; pick the closest one
+fn nearest_scene_duration(seconds: f64) -> &'static str {
+    if seconds < 6.0 {
+        "4s"
+    } else if seconds < 10.0 {
+        "8s"
+    } else {
+        "12s"
+    }
+}
+
+/// Report silent, black and frozen ranges for timeline clips
+///
+/// Analyses `clip_ids`, or every clip when omitted. `kinds` limits which range
+/// kinds count as dead air for the suggested trims (default: all). With
+/// `auto_trim`, clips whose head or tail is dead air get new trim points and
+/// the timeline is re-laid out.
+#[tauri::command]
+pub async fn analyze_dead_air(
+    app: AppHandle,
+    project_name: String,
+    clip_ids: Option<Vec<String>>,
+    kinds: Option<Vec<String>>,
+    auto_trim: Option<bool>,
+) -> Result<Vec<ClipDeadAirReport>, String> {
+    verify_ffmpeg_available(Some(&app))?;
+
+    // Load editor state to get clip information
+    let editor_state = load_editor_state(app.clone(), project_name.clone())
+        .await
+        .map_err(|e| format!("Failed to load editor state: {}", e))?;
+
+    let mut state = editor_state.ok_or("Editor state not found")?;
+    let auto_trim = auto_trim.unwrap_or(false);
+
+    let mut reports = Vec::new();
+    let mut changed = false;
+
+    for clip in state.clips.iter_mut() {
+        if let Some(ids) = &clip_ids {
+            if !ids.contains(&clip.id) {
+                continue;
+            }
+        }
+
+        let ranges = detect_dead_air(&app, &clip.video_path, clip.trim_start, clip.trim_end).await?;
+
+        let trim_ranges: Vec<_> = ranges
+            .iter()
+            .filter(|r| kinds.as_ref().is_none_or(|k| k.contains(&r.kind)))
+            .cloned()
+            .collect();
+        let (suggested_start, suggested_end) = dead_air_trim(&trim_ranges, clip.trim_start, clip.trim_end);
+
+        // Never trim a clip away entirely
+        let keeps_content = suggested_end - suggested_start >= MIN_AUTO_TRIM_DURATION;
+        let has_dead_edges = suggested_start > clip.trim_start || suggested_end < clip.trim_end;
+        let trimmed = auto_trim && keeps_content && has_dead_edges;
+
+        if trimmed {
+            clip.trim_start = suggested_start;
+            clip.trim_end = suggested_end;
+            clip.duration = suggested_end - suggested_start;
+            changed = true;
+        }
+
+        reports.push(ClipDeadAirReport {
+            clip_id: clip.id.clone(),
+            ranges,
+            suggested_trim_start: suggested_start,
+            suggested_trim_end: suggested_end,
+            trimmed,
+        });
+    }
+
+    if changed {
+        recompute_positions(&mut state.clips);
+        save_editor_state(app.clone(), project_name.clone(), state).await?;
+    }
+
+    Ok(reports)
+}
+
 /// Import a video file into the project
+///
+/// `mode` is `"copy"` (default) to copy the file into `videos/`, or
+/// `"reference"` to use the file in place and record its path, size,
+/// modification time and hash so it can be relinked if it moves.
+///
+/// If the project already has an import with identical content, the existing
+/// asset is returned with `"duplicate": true` and the picked file as
+/// `"sourcePath"` instead of importing again, unless `on_duplicate` is
+/// `"import"`.
 #[tauri::command]
 pub async fn import_video(
     app: AppHandle,
     project_name: String,
     file_path: Option<String>,
+    mode: Option<String>,
+    on_duplicate: Option<String>,
 ) -> Result<serde_json::Value, String> {
     verify_ffmpeg_available(Some(&app))?;
 
+    let reference = parse_import_mode(mode.as_deref())?;
+
     // Open file dialog if no file path provided
     let source_path: std::path::PathBuf = if let Some(path) = file_path {
         std::path::PathBuf::from(path)
@@ -245,7 +724,7 @@ pub async fn import_video(
         let file_path = app
             .dialog()
             .file()
-            .add_filter("Video Files", &["mp4", "mkv", "mov", "avi", "webm"])
+            .add_filter("Video Files", &VIDEO_EXTENSIONS)
             .add_filter("MP4 Video", &["mp4"])
             .add_filter("All Files", &["*"])
             .blocking_pick_file();
@@ -256,171 +735,670 @@ pub async fn import_video(
         }
     };
 
-    // Get video duration using FFprobe
-    let duration = get_video_duration(&app, source_path.to_str().unwrap_or("")).await?;
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    let allow_duplicates = on_duplicate.as_deref() == Some("import");
+    let context = ImportContext::new(paths.root().to_path_buf(), &meta, reference, allow_duplicates);
+
+    let record = match import_file(&app, &context, &source_path).await? {
+        ImportOutcome::Imported(record) => record,
+        ImportOutcome::Duplicate(existing_id) => {
+            let existing = meta
+                .imported_videos
+                .iter()
+                .find(|r| r.id == existing_id)
+                .ok_or(format!("Imported video {} not found", existing_id))?;
+            let mut metadata = imported_video_json(existing, false);
+            metadata["duplicate"] = serde_json::Value::Bool(true);
+            metadata["sourcePath"] = source_path.to_string_lossy().into();
+            return Ok(metadata);
+        }
+    };
+
+    let use_proxies = update_project_meta(paths.root(), |meta| {
+        meta.imported_videos.push(record.clone());
+        Ok(meta.use_proxies)
+    })?;
+
+    if use_proxies {
+        spawn_proxy_generation(
+            app.clone(),
+            paths.root().to_path_buf(),
+            vec![(record.file_path.clone(), record.proxy_key().to_string())],
+        );
+    }
+
+    Ok(imported_video_json(&record, false))
+}
+
+/// Import several files, or a folder of videos, processing files concurrently
+///
+/// Sources are `file_paths`, or every video in `folder` (recursing when
+/// `recursive` is set, filtered by `extensions`). With neither, a file picker
+/// (or a folder picker when `pick_folder` is set) is opened. At most
+/// `concurrency` files are processed at once. An `import-progress` event is
+/// emitted as each file starts and finishes. Failures don't stop the batch;
+/// they're reported in the returned summary along with skipped duplicates.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn import_videos(
+    app: AppHandle,
+    project_name: String,
+    file_paths: Option<Vec<String>>,
+    folder: Option<String>,
+    recursive: Option<bool>,
+    extensions: Option<Vec<String>>,
+    pick_folder: Option<bool>,
+    mode: Option<String>,
+    on_duplicate: Option<String>,
+    concurrency: Option<usize>,
+) -> Result<ImportSummary, String> {
+    verify_ffmpeg_available(Some(&app))?;
+
+    let reference = parse_import_mode(mode.as_deref())?;
+    let extensions: Vec<String> =
+        extensions.unwrap_or_else(|| VIDEO_EXTENSIONS.iter().map(|e| e.to_string()).collect());
+
+    let folder = match (file_paths.is_some(), folder, pick_folder.unwrap_or(false)) {
+        (false, None, true) => match app.dialog().file().blocking_pick_folder() {
+            Some(dir) => Some(PathBuf::from(dir.to_string())),
+            None => return Err("Folder selection cancelled".to_string()),
+        },
+        (_, folder, _) => folder.map(PathBuf::from),
+    };
+
+    let mut sources: Vec<PathBuf> = match (file_paths, folder) {
+        (Some(files), _) => files.into_iter().map(PathBuf::from).collect(),
+        (None, Some(folder)) => {
+            collect_folder_videos(&folder, recursive.unwrap_or(false), &extensions)?
+        }
+        (None, None) => {
+            let ext_refs: Vec<&str> = extensions.iter().map(String::as_str).collect();
+            match app
+                .dialog()
+                .file()
+                .add_filter("Video Files", &ext_refs)
+                .add_filter("All Files", &["*"])
+                .blocking_pick_files()
+            {
+                Some(files) => files
+                    .into_iter()
+                    .map(|f| PathBuf::from(f.to_string()))
+                    .collect(),
+                None => return Err("File selection cancelled".to_string()),
+            }
+        }
+    };
+    // Keep the first occurrence of each path, in the order given
+    let mut seen = HashSet::new();
+    sources.retain(|source| seen.insert(source.clone()));
 
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    
-    // Ensure videos directory exists
-    std::fs::create_dir_all(paths.videos_dir())
-        .map_err(|e| format!("Failed to create videos directory: {}", e))?;
+    let meta = read_project_meta(paths.root())?;
+    let allow_duplicates = on_duplicate.as_deref() == Some("import");
+    let context = Arc::new(ImportContext::new(
+        paths.root().to_path_buf(),
+        &meta,
+        reference,
+        allow_duplicates,
+    ));
+
+    let total = sources.len();
+    let queue = Arc::new(Mutex::new(
+        sources.into_iter().enumerate().collect::<VecDeque<_>>(),
+    ));
+    let workers = concurrency
+        .unwrap_or(DEFAULT_IMPORT_CONCURRENCY)
+        .clamp(1, MAX_IMPORT_CONCURRENCY)
+        .min(total.max(1));
+
+    let mut handles = Vec::new();
+    for _ in 0..workers {
+        let app = app.clone();
+        let queue = queue.clone();
+        let context = context.clone();
+
+        handles.push(tauri::async_runtime::spawn(async move {
+            let mut results = Vec::new();
+            loop {
+                let next = queue.lock().ok().and_then(|mut q| q.pop_front());
+                let Some((index, source)) = next else {
+                    break;
+                };
+
+                emit_import_progress(&app, index, total, &source, "started", None, None);
+                let outcome = import_file(&app, &context, &source).await;
+                match &outcome {
+                    Ok(ImportOutcome::Imported(record)) => emit_import_progress(
+                        &app,
+                        index,
+                        total,
+                        &source,
+                        "imported",
+                        Some(&record.id),
+                        None,
+                    ),
+                    Ok(ImportOutcome::Duplicate(existing)) => emit_import_progress(
+                        &app,
+                        index,
+                        total,
+                        &source,
+                        "skipped",
+                        Some(existing),
+                        None,
+                    ),
+                    Err(e) => {
+                        emit_import_progress(&app, index, total, &source, "failed", None, Some(e))
+                    }
+                }
+                results.push((index, source, outcome));
+            }
+            results
+        }));
+    }
 
-    // Generate unique ID for the imported video
-    let video_id = format!("imported_{}", Uuid::new_v4().to_string().replace("-", ""));
-    let dest_path = paths.video_file(&video_id);
+    let mut results = Vec::new();
+    for handle in handles {
+        results.extend(
+            handle
+                .await
+                .map_err(|e| format!("Import worker failed: {}", e))?,
+        );
+    }
+    results.sort_by_key(|(index, _, _)| *index);
 
-    // Copy file to project videos directory
-    fs::copy(&source_path, &dest_path)
-        .map_err(|e| format!("Failed to copy video file: {}", e))?;
+    let mut summary = ImportSummary {
+        imported: Vec::new(),
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut records = Vec::new();
+
+    for (_, source, outcome) in results {
+        let path = source.to_string_lossy().to_string();
+        match outcome {
+            Ok(ImportOutcome::Imported(record)) => {
+                summary.imported.push(imported_video_json(&record, false));
+                records.push(record);
+            }
+            Ok(ImportOutcome::Duplicate(existing_id)) => summary.skipped.push(ImportSkip {
+                path,
+                reason: "duplicate".to_string(),
+                existing_video_id: Some(existing_id),
+            }),
+            Err(error) => summary.failed.push(ImportFailure { path, error }),
+        }
+    }
 
-    // Get original filename for display name
-    let original_name = source_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Imported Video")
-        .to_string();
+    if !records.is_empty() {
+        let sources: Vec<(String, String)> = records
+            .iter()
+            .map(|r| (r.file_path.clone(), r.proxy_key().to_string()))
+            .collect();
+        let use_proxies = update_project_meta(paths.root(), |meta| {
+            meta.imported_videos.extend(records);
+            Ok(meta.use_proxies)
+        })?;
+
+        if use_proxies {
+            spawn_proxy_generation(app.clone(), paths.root().to_path_buf(), sources);
+        }
+    }
 
-    // Remove extension from name
-    let display_name = original_name
-        .rsplit('.')
-        .nth(1)
-        .unwrap_or(&original_name)
-        .to_string();
+    Ok(summary)
+}
 
-    // Return metadata
-    let metadata = serde_json::json!({
-        "id": video_id,
+fn emit_import_progress(
+    app: &AppHandle,
+    index: usize,
+    total: usize,
+    path: &Path,
+    status: &str,
+    video_id: Option<&str>,
+    error: Option<&str>,
+) {
+    let _ = app.emit(
+        "import-progress",
+        serde_json::json!({
+            "index": index,
+            "total": total,
+            "path": path.to_string_lossy(),
+            "status": status,
+            "videoId": video_id,
+            "error": error,
+        }),
+    );
+}
+
+/// Metadata returned to the UI for an imported video
+fn imported_video_json(record: &ImportedVideoMeta, missing: bool) -> serde_json::Value {
+    // Remove extension from name
+    let display_name = Path::new(&record.original_name)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.is_empty())
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| {
+            let uuid_part = record.id.strip_prefix("imported_").unwrap_or(&record.id);
+            format!("Imported Video ({})", &uuid_part[..uuid_part.len().min(8)])
+        });
+
+    serde_json::json!({
+        "id": record.id,
         "name": display_name,
-        "videoPath": dest_path.to_str().unwrap_or(""),
-        "originalDuration": duration,
-        "createdAt": std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64,
-    });
+        "originalName": record.original_name,
+        "videoPath": record.file_path,
+        "originalDuration": record.probe.duration,
+        "width": record.probe.width,
+        "height": record.probe.height,
+        "createdAt": record.imported_at,
+        "reference": record.reference,
+        "missing": missing,
+        "tags": record.tags,
+    })
+}
 
-    Ok(metadata)
+/// Build an import record for a file in videos/ that predates import metadata
+async fn backfill_imported_video(
+    app: &AppHandle,
+    video_id: &str,
+    path: &Path,
+) -> Result<ImportedVideoMeta, String> {
+    let probe = probe_video(app, path.to_str().unwrap_or("")).await?;
+    let (size, modified_at) = file_fingerprint(path)?;
+
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    let imported_at = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .map_err(|e| format!("Failed to get file timestamp: {}", e))?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    Ok(ImportedVideoMeta {
+        id: video_id.to_string(),
+        // The original filename of legacy imports was never stored
+        original_name: String::new(),
+        file_path: path.to_string_lossy().to_string(),
+        reference: false,
+        size,
+        modified_at,
+        hash: hash_file(path)?,
+        probe,
+        imported_at,
+        tags: Vec::new(),
+    })
 }
 
 /// List all imported videos for a project
+///
+/// Listings come from the import records in project metadata. Files in
+/// `videos/` imported before records were kept are probed once and recorded.
 #[tauri::command]
 pub async fn list_imported_videos(
     app: AppHandle,
     project_name: String,
 ) -> Result<Vec<serde_json::Value>, String> {
-    verify_ffmpeg_available(Some(&app))?;
-
     let paths = ProjectPaths::from_name(&app, &project_name)?;
     let videos_dir = paths.videos_dir();
+    let mut meta = read_project_meta(paths.root())?;
 
-    if !videos_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut imported_videos = Vec::new();
+    // Record any legacy imports that are on disk but not in metadata
+    let mut backfilled = Vec::new();
+    if videos_dir.exists() {
+        let entries = std::fs::read_dir(&videos_dir)
+            .map_err(|e| format!("Failed to read videos directory: {}", e))?;
 
-    // Read directory entries
-    let entries = std::fs::read_dir(&videos_dir)
-        .map_err(|e| format!("Failed to read videos directory: {}", e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+            let path = entry.path();
 
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+            let file_name = path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("");
 
-        // Only process files (not directories)
-        if !path.is_file() {
-            continue;
-        }
+            if !path.is_file() || !file_name.starts_with("imported_") {
+                continue;
+            }
 
-        // Check if filename starts with "imported_"
-        let file_name = path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("");
+            // Extract video ID (remove .mp4 extension if present)
+            let video_id = file_name.strip_suffix(".mp4").unwrap_or(file_name);
+            if meta.imported_videos.iter().any(|r| r.id == video_id) {
+                continue;
+            }
 
-        if !file_name.starts_with("imported_") {
-            continue;
+            verify_ffmpeg_available(Some(&app))?;
+            match backfill_imported_video(&app, video_id, &path).await {
+                Ok(record) => backfilled.push(record),
+                Err(e) => eprintln!("Failed to read imported video {}: {}", file_name, e),
+            }
         }
+    }
 
-        // Extract video ID (remove .mp4 extension if present)
-        let video_id = if file_name.ends_with(".mp4") {
-            &file_name[..file_name.len() - 4]
-        } else {
-            file_name
-        }.to_string();
-
-        // Get video duration using FFprobe
-        let duration = match get_video_duration(&app, path.to_str().unwrap_or("")).await {
-            Ok(d) => d,
-            Err(e) => {
-                eprintln!("Failed to get duration for {}: {}", file_name, e);
-                continue; // Skip videos we can't read
+    // Probing happens unlocked, so skip records another import added meanwhile
+    if !backfilled.is_empty() {
+        meta = update_project_meta(paths.root(), |meta| {
+            for record in backfilled {
+                if !meta.imported_videos.iter().any(|r| r.id == record.id) {
+                    meta.imported_videos.push(record);
+                }
             }
-        };
+            Ok(meta.clone())
+        })?;
+    }
 
-        // Get file metadata
-        let metadata = std::fs::metadata(&path)
-            .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-        
-        let created_at = metadata
-            .created()
-            .or_else(|_| metadata.modified())
-            .map_err(|e| format!("Failed to get file timestamp: {}", e))?
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() as i64;
-
-        // Extract display name from filename (remove "imported_" prefix and extension)
-        // Since we don't store the original filename, use a default name with UUID suffix for uniqueness
-        let display_name = if video_id.starts_with("imported_") {
-            let uuid_part = video_id.strip_prefix("imported_").unwrap_or("");
-            if uuid_part.len() >= 8 {
-                format!("Imported Video ({})", &uuid_part[..8])
-            } else {
-                format!("Imported Video")
-            }
-        } else {
-            video_id.clone()
-        };
+    let mut imported_videos: Vec<(i64, serde_json::Value)> = Vec::new();
+    for record in &meta.imported_videos {
+        let path = Path::new(&record.file_path);
+        let missing = !path.exists();
 
-        imported_videos.push(serde_json::json!({
-            "id": video_id,
-            "name": display_name,
-            "videoPath": path.to_str().unwrap_or(""),
-            "originalDuration": duration,
-            "createdAt": created_at,
-        }));
+        // Referenced files live outside the workspace; re-allow them after a restart
+        if record.reference && !missing {
+            let _ = app.asset_protocol_scope().allow_file(path);
+        }
+
+        imported_videos.push((record.imported_at, imported_video_json(record, missing)));
     }
 
-    // Sort by creation time (newest first)
-    imported_videos.sort_by(|a, b| {
-        let a_time = a.get("createdAt").and_then(|v| v.as_i64()).unwrap_or(0);
-        let b_time = b.get("createdAt").and_then(|v| v.as_i64()).unwrap_or(0);
-        b_time.cmp(&a_time)
-    });
+    // Sort by import time (newest first)
+    imported_videos.sort_by_key(|(imported_at, _)| std::cmp::Reverse(*imported_at));
 
-    Ok(imported_videos)
+    Ok(imported_videos.into_iter().map(|(_, video)| video).collect())
 }
 
-/// Delete an imported video file from the project
+/// Replace the user tags on an imported video
 #[tauri::command]
-pub async fn delete_imported_video(
+pub async fn set_imported_video_tags(
     app: AppHandle,
     project_name: String,
     video_id: String,
+    tags: Vec<String>,
 ) -> Result<(), String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    update_project_meta(paths.root(), |meta| {
+        let record = meta
+            .imported_videos
+            .iter_mut()
+            .find(|r| r.id == video_id)
+            .ok_or(format!("Imported video {} not found", video_id))?;
+
+        record.tags = normalize_tags(&tags);
+        Ok(())
+    })
+}
+
+/// Delete an imported video file from the project, returning the ids of
+/// timeline clips removed with it
+///
+/// Clips using the file are handled as in `delete_video_from_project`.
+/// Referenced imports only drop their record, so their clips are left alone.
+#[tauri::command]
+pub async fn delete_imported_video(
+    app: AppHandle,
+    project_name: String,
+    video_id: String,
+    remove_clips: Option<bool>,
+) -> Result<Vec<String>, String> {
     if !video_id.starts_with("imported_") {
         return Err("Can only delete imported videos".to_string());
     }
 
     let paths = ProjectPaths::from_name(&app, &project_name)?;
-    let video_path = paths.video_file(&video_id);
-
-    if video_path.exists() {
-        std::fs::remove_file(&video_path)
-            .map_err(|e| format!("Failed to delete imported video: {}", e))?;
+    let _lock = lock_project(paths.root())?;
+
+    // Referenced files stay where they are, so clips using them keep working
+    let referenced = read_project_meta(paths.root())?
+        .imported_videos
+        .iter()
+        .any(|r| r.id == video_id && r.reference);
+    let removed_clips = if referenced {
+        Vec::new()
     } else {
-        return Err("Video not found".to_string());
+        repair::guard_timeline_usage(paths.root(), &paths.video_file(&video_id), remove_clips)?
+    };
+
+    update_project_meta(paths.root(), |meta| {
+        let record = meta
+            .imported_videos
+            .iter()
+            .position(|r| r.id == video_id)
+            .map(|index| meta.imported_videos.remove(index));
+
+        // Referenced files belong to the user: only forget the record
+        if record.as_ref().is_some_and(|r| r.reference) {
+            return Ok(());
+        }
+
+        let video_path = paths.video_file(&video_id);
+
+        if video_path.exists() {
+            let record = record.map(RecycledRecord::ImportedVideo);
+            recycle::recycle_project_file(paths.root(), "video", &video_path, record)?;
+        } else if record.is_none() {
+            return Err("Video not found".to_string());
+        }
+
+        Ok(())
+    })?;
+
+    Ok(removed_clips)
+}
+
+/// Turn proxy editing on or off for a project
+///
+/// Enabling it starts background generation of any missing proxies.
+#[tauri::command]
+pub async fn set_use_proxies(
+    app: AppHandle,
+    project_name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    update_project_meta(paths.root(), |meta| {
+        meta.use_proxies = enabled;
+        Ok(())
+    })?;
+
+    if enabled {
+        generate_proxies(app, project_name).await?;
     }
 
     Ok(())
 }
+
+/// Start background proxy generation for every video in the project
+///
+/// Covers the files in `videos/` and referenced imports that can be found.
+/// Returns the number of videos queued; existing proxies are skipped.
+#[tauri::command]
+pub async fn generate_proxies(app: AppHandle, project_name: String) -> Result<usize, String> {
+    verify_ffmpeg_available(Some(&app))?;
+
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    let videos_dir = paths.videos_dir();
+
+    let mut files = Vec::new();
+    if videos_dir.exists() {
+        let entries = std::fs::read_dir(&videos_dir)
+            .map_err(|e| format!("Failed to read videos directory: {}", e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+            files.push(entry.path());
+        }
+    }
+    files.extend(
+        meta.imported_videos
+            .iter()
+            .filter(|r| r.reference)
+            .map(|r| PathBuf::from(&r.file_path)),
+    );
+
+    // Files with the same content share one proxy
+    let mut keys = HashSet::new();
+    let mut sources = Vec::new();
+    for path in files.into_iter().filter(|path| path.is_file()) {
+        let Some(key) = meta.proxy_key(&path) else {
+            continue;
+        };
+        if !paths.proxy_file(&key).exists() && keys.insert(key.clone()) {
+            sources.push((path.to_string_lossy().to_string(), key));
+        }
+    }
+
+    let queued = sources.len();
+    if queued > 0 {
+        spawn_proxy_generation(app, paths.root().to_path_buf(), sources);
+    }
+
+    Ok(queued)
+}
+
+/// Find media used by the project that is missing or has changed on disk
+///
+/// Checks referenced imports against their recorded size and modification
+/// time, and every timeline clip's source file.
+#[tauri::command]
+pub async fn find_missing_media(
+    app: AppHandle,
+    project_name: String,
+) -> Result<Vec<MissingMedia>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    let clips = load_editor_state(app.clone(), project_name.clone())
+        .await?
+        .map(|state| state.clips)
+        .unwrap_or_default();
+
+    let clip_ids_for = |path: &str| -> Vec<String> {
+        clips
+            .iter()
+            .filter(|c| c.video_path == path)
+            .map(|c| c.id.clone())
+            .collect()
+    };
+
+    let mut missing = Vec::new();
+
+    for record in meta.imported_videos.iter().filter(|r| r.reference) {
+        let path = Path::new(&record.file_path);
+        let reason = if !path.exists() {
+            "missing"
+        } else if file_fingerprint(path)? != (record.size, record.modified_at) {
+            "changed"
+        } else {
+            continue;
+        };
+
+        missing.push(MissingMedia {
+            video_id: Some(record.id.clone()),
+            path: record.file_path.clone(),
+            reason: reason.to_string(),
+            clip_ids: clip_ids_for(&record.file_path),
+        });
+    }
+
+    for clip in &clips {
+        let already_reported = missing.iter().any(|m| m.path == clip.video_path);
+        if already_reported || Path::new(&clip.video_path).exists() {
+            continue;
+        }
+        missing.push(MissingMedia {
+            video_id: None,
+            path: clip.video_path.clone(),
+            reason: "missing".to_string(),
+            clip_ids: clip_ids_for(&clip.video_path),
+        });
+    }
+
+    Ok(missing)
+}
+
+/// Relink a referenced video that has moved by searching a folder for it
+///
+/// Files with the same name are checked first, then any file of the same
+/// size; a candidate only matches if its content hash equals the recorded
+/// one. On success the import record and all timeline clips using the old
+/// path are updated. Returns the new path, or `None` if no match was found.
+#[tauri::command]
+pub async fn relink_media(
+    app: AppHandle,
+    project_name: String,
+    video_id: String,
+    search_dir: Option<String>,
+) -> Result<Option<String>, String> {
+    let paths = ProjectPaths::from_name(&app, &project_name)?;
+    let meta = read_project_meta(paths.root())?;
+    let record = meta
+        .imported_videos
+        .iter()
+        .find(|r| r.id == video_id && r.reference)
+        .cloned()
+        .ok_or(format!("Referenced video {} not found", video_id))?;
+
+    // Open folder dialog if no search directory provided
+    let search_dir = match search_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => match app.dialog().file().blocking_pick_folder() {
+            Some(dir) => PathBuf::from(dir.to_string()),
+            None => return Err("Folder selection cancelled".to_string()),
+        },
+    };
+
+    let mut files = Vec::new();
+    collect_files(&search_dir, &mut files)?;
+
+    let original_name = Path::new(&record.file_path).file_name().map(|n| n.to_os_string());
+    let (same_name, others): (Vec<PathBuf>, Vec<PathBuf>) = files
+        .into_iter()
+        .partition(|f| f.file_name().map(|n| n.to_os_string()) == original_name);
+
+    let mut found: Option<PathBuf> = None;
+    for candidate in same_name.into_iter().chain(others) {
+        let Ok((size, _)) = file_fingerprint(&candidate) else {
+            continue;
+        };
+        if size == record.size && hash_file(&candidate)? == record.hash {
+            found = Some(candidate);
+            break;
+        }
+    }
+
+    let Some(new_path) = found else {
+        return Ok(None);
+    };
+
+    let new_path = new_path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve video path: {}", e))?;
+    let new_path_str = new_path.to_string_lossy().to_string();
+    let (size, modified_at) = file_fingerprint(&new_path)?;
+
+    app.asset_protocol_scope()
+        .allow_file(&new_path)
+        .map_err(|e| format!("Failed to allow access to video file: {}", e))?;
+
+    update_project_meta(paths.root(), |meta| {
+        if let Some(entry) = meta.imported_videos.iter_mut().find(|r| r.id == video_id) {
+            entry.file_path = new_path_str.clone();
+            entry.size = size;
+            entry.modified_at = modified_at;
+        }
+        Ok(())
+    })?;
+
+    if let Some(mut state) = load_editor_state(app.clone(), project_name.clone()).await? {
+        let mut changed = false;
+        for clip in state.clips.iter_mut().filter(|c| c.video_path == record.file_path) {
+            clip.video_path = new_path_str.clone();
+            changed = true;
+        }
+        if changed {
+            save_editor_state(app.clone(), project_name.clone(), state).await?;
+        }
+    }
+
+    Ok(Some(new_path_str))
+}
@@ -0,0 +1,339 @@
+use tauri::AppHandle;
+use crate::commands::video_editor::ffmpeg::ffmpeg::run_ffmpeg_log;
+use crate::commands::video_editor::types::{DeadAirRange, SceneCut};
+
+/// Default scene-change score above which a frame is considered a cut
+pub const DEFAULT_SCENE_THRESHOLD: f64 = 0.3;
+
+/// Audio below this level counts as silence
+const SILENCE_NOISE: &str = "-50dB";
+/// Minimum length of a silent, black or frozen range, in seconds
+const DEAD_AIR_MIN_DURATION: f64 = 0.3;
+/// Ranges starting or ending this close to a clip edge count as touching it
+const EDGE_TOLERANCE: f64 = 0.05;
+
+/// Detect hard cuts in a section of a video using ffmpeg's scene score.
+///
+/// Every frame whose `scene` score exceeds `threshold` is printed by the
+/// `metadata` filter; cut times are returned in source time (offset by `start`).
+pub async fn detect_scene_changes(
+    app: &AppHandle,
+    video_path: &str,
+    start: f64,
+    end: f64,
+    threshold: f64,
+) -> Result<Vec<SceneCut>, String> {
+    let duration = end - start;
+
+    if duration <= 0.0 {
+        return Err("Invalid duration: trim_end must be greater than trim_start".to_string());
+    }
+
+    let start_str = start.to_string();
+    let duration_str = duration.to_string();
+    let filter = format!("select='gt(scene,{})',metadata=print", threshold);
+
+    let args = vec![
+        "-hide_banner",
+        "-ss", &start_str,
+        "-i", video_path,
+        "-t", &duration_str,
+        "-an",
+        "-vf", &filter,
+        "-f", "null",
+        "-",
+    ];
+
+    let log = run_ffmpeg_log(app, &args, "detect scenes").await?;
+
+    Ok(parse_scene_scores(&log)
+        .into_iter()
+        .map(|(time, score)| SceneCut {
+            time: start + time,
+            score,
+        })
+        .collect())
+}
+
+/// Find silent, black and frozen ranges in a section of a video in a single pass.
+///
+/// Range times are returned in source time (offset by `start`).
+pub async fn detect_dead_air(
+    app: &AppHandle,
+    video_path: &str,
+    start: f64,
+    end: f64,
+) -> Result<Vec<DeadAirRange>, String> {
+    let duration = end - start;
+
+    if duration <= 0.0 {
+        return Err("Invalid duration: trim_end must be greater than trim_start".to_string());
+    }
+
+    let start_str = start.to_string();
+    let duration_str = duration.to_string();
+    let audio_filter = format!("silencedetect=noise={}:d={}", SILENCE_NOISE, DEAD_AIR_MIN_DURATION);
+    let video_filter = format!(
+        "blackdetect=d={}:pix_th=0.10,freezedetect=n=-60dB:d={}",
+        DEAD_AIR_MIN_DURATION, DEAD_AIR_MIN_DURATION
+    );
+
+    // -af is ignored when the input has no audio stream
+    let args = vec![
+        "-hide_banner",
+        "-ss", &start_str,
+        "-i", video_path,
+        "-t", &duration_str,
+        "-af", &audio_filter,
+        "-vf", &video_filter,
+        "-f", "null",
+        "-",
+    ];
+
+    let log = run_ffmpeg_log(app, &args, "detect dead air").await?;
+
+    Ok(parse_dead_air(&log, duration)
+        .into_iter()
+        .map(|range| DeadAirRange {
+            start: start + range.start,
+            end: start + range.end,
+            ..range
+        })
+        .collect())
+}
+
+/// Parse silencedetect, blackdetect and freezedetect log lines into ranges.
+///
+/// Ranges still open at the end of the log (e.g. silence until the end) are closed at `duration`.
+fn parse_dead_air(log: &str, duration: f64) -> Vec<DeadAirRange> {
+    let mut ranges = Vec::new();
+    let mut silence_start: Option<f64> = None;
+    let mut freeze_start: Option<f64> = None;
+
+    for line in log.lines() {
+        if let Some(time) = value_after(line, "silence_start:") {
+            silence_start = Some(time);
+        } else if let Some(time) = value_after(line, "silence_end:") {
+            if let Some(range_start) = silence_start.take() {
+                ranges.push(dead_air_range("silence", range_start, time));
+            }
+        } else if let (Some(range_start), Some(range_end)) = (
+            value_after(line, "black_start:"),
+            value_after(line, "black_end:"),
+        ) {
+            ranges.push(dead_air_range("black", range_start, range_end));
+        } else if let Some(time) = value_after(line, "freeze_start:") {
+            freeze_start = Some(time);
+        } else if let Some(time) = value_after(line, "freeze_end:") {
+            if let Some(range_start) = freeze_start.take() {
+                ranges.push(dead_air_range("freeze", range_start, time));
+            }
+        }
+    }
+
+    if let Some(range_start) = silence_start {
+        ranges.push(dead_air_range("silence", range_start, duration));
+    }
+    if let Some(range_start) = freeze_start {
+        ranges.push(dead_air_range("freeze", range_start, duration));
+    }
+
+    ranges.sort_by(|a, b| a.start.total_cmp(&b.start));
+    ranges
+}
+
+fn dead_air_range(kind: &str, start: f64, end: f64) -> DeadAirRange {
+    DeadAirRange {
+        kind: kind.to_string(),
+        start: start.max(0.0),
+        end,
+    }
+}
+
+/// Work out new trim points that drop dead air touching the head and tail of `start..end`.
+///
+/// Overlapping or adjacent ranges are chained, so black frames followed by silence are both trimmed.
+pub fn dead_air_trim(ranges: &[DeadAirRange], start: f64, end: f64) -> (f64, f64) {
+    let mut new_start = start;
+    loop {
+        let extended = ranges
+            .iter()
+            .filter(|r| r.start <= new_start + EDGE_TOLERANCE && r.end > new_start)
+            .map(|r| r.end)
+            .fold(new_start, f64::max);
+        if extended <= new_start {
+            break;
+        }
+        new_start = extended;
+    }
+
+    let mut new_end = end;
+    loop {
+        let extended = ranges
+            .iter()
+            .filter(|r| r.end >= new_end - EDGE_TOLERANCE && r.start < new_end)
+            .map(|r| r.start)
+            .fold(new_end, f64::min);
+        if extended >= new_end {
+            break;
+        }
+        new_end = extended;
+    }
+
+    (new_start.min(end), new_end.max(start))
+}
+
+/// Parse `metadata=print` output into (pts_time, scene_score) pairs.
+///
+/// The filter logs a `frame:.. pts:.. pts_time:..` line followed by
+/// one `key=value` line per metadata entry.
+fn parse_scene_scores(log: &str) -> Vec<(f64, f64)> {
+    let mut results = Vec::new();
+    let mut current_time: Option<f64> = None;
+
+    for line in log.lines() {
+        if let Some(time) = value_after(line, "pts_time:") {
+            current_time = Some(time);
+        } else if let Some(score) = value_after(line, "lavfi.scene_score=") {
+            if let Some(time) = current_time.take() {
+                results.push((time, score));
+            }
+        }
+    }
+
+    results
+}
+
+/// Parse the number that follows `key` in a log line
+fn value_after(line: &str, key: &str) -> Option<f64> {
+    let start = line.find(key)? + key.len();
+    line[start..]
+        .trim_start()
+        .split(|c: char| c.is_whitespace() || c == '|')
+        .next()
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_scene_scores_with_the_preceding_frame_time() {
+        let log = "\
+[Parsed_metadata_1 @ 0x1] frame:12   pts:12288   pts_time:0.48
+[Parsed_metadata_1 @ 0x1] lavfi.scene_score=0.412
+[Parsed_metadata_1 @ 0x1] frame:97   pts:99328   pts_time:3.88
+[Parsed_metadata_1 @ 0x1] lavfi.scene_score=0.87
+";
+        assert_eq!(parse_scene_scores(log), vec![(0.48, 0.412), (3.88, 0.87)]);
+    }
+
+    #[test]
+    fn skips_scores_without_a_frame_time() {
+        let log = "\
+lavfi.scene_score=0.5
+frame:3 pts:3072 pts_time:0.12
+lavfi.scene_score=0.6
+lavfi.scene_score=0.7
+";
+        assert_eq!(parse_scene_scores(log), vec![(0.12, 0.6)]);
+    }
+
+    fn range(kind: &str, start: f64, end: f64) -> DeadAirRange {
+        DeadAirRange {
+            kind: kind.to_string(),
+            start,
+            end,
+        }
+    }
+
+    fn spans(ranges: &[DeadAirRange]) -> Vec<(&str, f64, f64)> {
+        ranges
+            .iter()
+            .map(|r| (r.kind.as_str(), r.start, r.end))
+            .collect()
+    }
+
+    #[test]
+    fn parses_silence_black_and_freeze_ranges_in_start_order() {
+        let log = "\
+[silencedetect @ 0x1] silence_start: 6.5
+[silencedetect @ 0x1] silence_end: 7.25 | silence_duration: 0.75
+[blackdetect @ 0x2] black_start:0 black_end:0.8 black_duration:0.8
+[freezedetect @ 0x3] lavfi.freezedetect.freeze_start: 3.1
+[freezedetect @ 0x3] lavfi.freezedetect.freeze_duration: 1.2
+[freezedetect @ 0x3] lavfi.freezedetect.freeze_end: 4.3
+";
+        assert_eq!(
+            spans(&parse_dead_air(log, 10.0)),
+            vec![
+                ("black", 0.0, 0.8),
+                ("freeze", 3.1, 4.3),
+                ("silence", 6.5, 7.25)
+            ]
+        );
+    }
+
+    #[test]
+    fn closes_open_ranges_at_the_duration() {
+        let log = "\
+[silencedetect @ 0x1] silence_start: 8.5
+[freezedetect @ 0x3] lavfi.freezedetect.freeze_start: 9
+";
+        assert_eq!(
+            spans(&parse_dead_air(log, 10.0)),
+            vec![("silence", 8.5, 10.0), ("freeze", 9.0, 10.0)]
+        );
+    }
+
+    #[test]
+    fn clamps_negative_starts_to_zero() {
+        let log = "\
+[silencedetect @ 0x1] silence_start: -0.01
+[silencedetect @ 0x1] silence_end: 1 | silence_duration: 1.01
+";
+        assert_eq!(
+            spans(&parse_dead_air(log, 5.0)),
+            vec![("silence", 0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn trims_dead_air_at_both_edges() {
+        let ranges = [range("black", 0.0, 0.8), range("silence", 8.0, 10.0)];
+        assert_eq!(dead_air_trim(&ranges, 0.0, 10.0), (0.8, 8.0));
+    }
+
+    #[test]
+    fn chains_overlapping_and_adjacent_ranges() {
+        // Black frames, then silence starting within the edge tolerance of their end
+        let ranges = [
+            range("silence", 0.83, 1.5),
+            range("black", 0.0, 0.8),
+            range("freeze", 7.0, 9.0),
+            range("silence", 8.5, 10.0),
+        ];
+        assert_eq!(dead_air_trim(&ranges, 0.0, 10.0), (1.5, 7.0));
+    }
+
+    #[test]
+    fn leaves_ranges_away_from_the_edges() {
+        let ranges = [range("silence", 0.2, 1.0), range("black", 4.0, 5.0)];
+        assert_eq!(dead_air_trim(&ranges, 0.0, 10.0), (0.0, 10.0));
+    }
+
+    #[test]
+    fn measures_edges_from_the_trim_points() {
+        // Relative to a clip trimmed to 2..6, not to the start of the source
+        let ranges = [range("black", 0.0, 1.0), range("silence", 1.98, 2.5)];
+        assert_eq!(dead_air_trim(&ranges, 2.0, 6.0), (2.5, 6.0));
+    }
+
+    #[test]
+    fn a_clip_that_is_all_dead_air_keeps_nothing() {
+        let ranges = [range("silence", 0.0, 10.0)];
+        let (start, end) = dead_air_trim(&ranges, 0.0, 10.0);
+        assert!(end - start <= 0.0);
+    }
+}
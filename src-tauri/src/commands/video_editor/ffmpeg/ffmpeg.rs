@@ -1,5 +1,6 @@
 use tauri::AppHandle;
 use tauri_plugin_shell::ShellExt;
+use crate::commands::projects::types::VideoProbe;
 
 /// Verify that FFmpeg sidecar is available
 pub fn verify_ffmpeg_available(app: Option<&AppHandle>) -> Result<(), String> {
@@ -29,16 +30,96 @@ pub async fn run_ffmpeg(app: &AppHandle, args: &[&str], op: &str) -> Result<(),
     }
 }
 
-/// Get video duration using FFprobe
-pub async fn get_video_duration(app: &AppHandle, video_path: &str) -> Result<f64, String> {
+/// Execute an FFmpeg command via sidecar and return its raw stdout
+pub async fn run_ffmpeg_output(app: &AppHandle, args: &[&str], op: &str) -> Result<Vec<u8>, String> {
+    let output = app
+        .shell()
+        .sidecar("ffmpeg")
+        .map_err(|e| format!("Failed to get ffmpeg sidecar: {}", e))?
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute ffmpeg for {}: {}", op, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("FFmpeg {} failed: {}", op, stderr))
+    } else {
+        Ok(output.stdout)
+    }
+}
+
+/// Execute an FFmpeg command via sidecar and return its log output (stderr)
+///
+/// Analysis filters such as `metadata=print` and `silencedetect` report through the log.
+pub async fn run_ffmpeg_log(app: &AppHandle, args: &[&str], op: &str) -> Result<String, String> {
+    let output = app
+        .shell()
+        .sidecar("ffmpeg")
+        .map_err(|e| format!("Failed to get ffmpeg sidecar: {}", e))?
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute ffmpeg for {}: {}", op, e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        Err(format!("FFmpeg {} failed: {}", op, stderr))
+    } else {
+        Ok(stderr)
+    }
+}
+
+/// Get the number of channels in the first audio stream using FFprobe
+///
+/// Returns 0 when the file has no audio stream.
+pub async fn get_audio_channels(app: &AppHandle, video_path: &str) -> Result<u32, String> {
+    let output = app
+        .shell()
+        .sidecar("ffprobe")
+        .map_err(|e| format!("Failed to get ffprobe sidecar: {}", e))?
+        .args([
+            "-v", "quiet",
+            "-print_format", "json",
+            "-show_streams",
+            "-select_streams", "a:0",
+            video_path,
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe failed: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .map_err(|e| format!("Failed to parse ffprobe JSON output: {}", e))?;
+
+    let channels = json
+        .get("streams")
+        .and_then(|s| s.as_array())
+        .and_then(|streams| streams.first())
+        .and_then(|stream| stream.get("channels"))
+        .and_then(|c| c.as_u64())
+        .unwrap_or(0);
+
+    Ok(channels as u32)
+}
+
+/// Read duration and stream information using FFprobe
+pub async fn probe_video(app: &AppHandle, video_path: &str) -> Result<VideoProbe, String> {
     let output = app
         .shell()
         .sidecar("ffprobe")
         .map_err(|e| format!("Failed to get ffprobe sidecar: {}", e))?
-        .args(&[
+        .args([
             "-v", "quiet",
             "-print_format", "json",
             "-show_format",
+            "-show_streams",
             video_path,
         ])
         .output()
@@ -64,6 +145,44 @@ pub async fn get_video_duration(app: &AppHandle, video_path: &str) -> Result<f64
         .parse()
         .map_err(|e| format!("Failed to parse duration '{}': {}", duration_str, e))?;
 
-    Ok(duration)
-}
+    let streams = json
+        .get("streams")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let stream_of_type = |codec_type: &str| {
+        streams
+            .iter()
+            .find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some(codec_type))
+    };
 
+    let video = stream_of_type("video");
+    let dimension = |key: &str| {
+        video
+            .and_then(|v| v.get(key))
+            .and_then(|d| d.as_u64())
+            .unwrap_or(0) as u32
+    };
+
+    // r_frame_rate is a fraction such as "30000/1001"
+    let frame_rate = video
+        .and_then(|v| v.get("r_frame_rate"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.split_once('/'))
+        .and_then(|(num, den)| Some((num.parse::<f64>().ok()?, den.parse::<f64>().ok()?)))
+        .filter(|(_, den)| *den > 0.0)
+        .map(|(num, den)| num / den);
+
+    Ok(VideoProbe {
+        duration,
+        width: dimension("width"),
+        height: dimension("height"),
+        video_codec: video
+            .and_then(|v| v.get("codec_name"))
+            .and_then(|c| c.as_str())
+            .map(|c| c.to_string()),
+        frame_rate,
+        has_audio: stream_of_type("audio").is_some(),
+    })
+}
@@ -3,9 +3,16 @@ pub mod filters;
 pub mod concat;
 pub mod waveform;
 pub mod sprite;
+pub mod peaks;
+pub mod analysis;
+pub mod proxy;
+pub mod remux;
 
-pub use ffmpeg::{verify_ffmpeg_available, get_video_duration};
+pub use ffmpeg::{verify_ffmpeg_available, probe_video};
 pub use concat::{concatenate_fast, concatenate_with_transitions};
 pub use waveform::generate_waveform_image;
 pub use sprite::generate_sprite_image;
-
+pub use peaks::generate_waveform_peaks;
+pub use proxy::generate_proxy;
+pub use remux::{export_format, remux, EXPORT_FORMATS};
+pub use analysis::{dead_air_trim, detect_dead_air, detect_scene_changes, DEFAULT_SCENE_THRESHOLD};
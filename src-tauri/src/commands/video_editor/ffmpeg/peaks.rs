@@ -0,0 +1,165 @@
+use tauri::AppHandle;
+use crate::commands::video_editor::ffmpeg::ffmpeg::{get_audio_channels, run_ffmpeg_output};
+use crate::commands::video_editor::types::{PeakLevel, WaveformPeaks};
+
+/// Sample rate used when decoding audio for peak extraction.
+/// Peaks only drive the timeline drawing, so full-rate audio is not needed.
+const PEAKS_SAMPLE_RATE: u32 = 16000;
+
+/// Samples per peak for each resolution level, from finest to coarsest
+const PEAK_LEVELS: [u32; 4] = [256, 1024, 4096, 16384];
+
+/// Decode the audio of a video to PCM and compute multi-resolution min/max peaks.
+///
+/// Peaks cover the whole source file so every clip cut from it can share them;
+/// the UI slices them by `trim_start`/`trim_end`. Returns `None` if the file has no audio.
+pub async fn generate_waveform_peaks(
+    app: &AppHandle,
+    video_path: &str,
+) -> Result<Option<WaveformPeaks>, String> {
+    let channels = get_audio_channels(app, video_path).await?;
+    if channels == 0 {
+        return Ok(None);
+    }
+
+    let channels_str = channels.to_string();
+    let sample_rate_str = PEAKS_SAMPLE_RATE.to_string();
+
+    // Raw interleaved signed 16-bit little-endian PCM on stdout
+    let args = vec![
+        "-i", video_path,
+        "-vn",
+        "-ac", &channels_str,
+        "-ar", &sample_rate_str,
+        "-f", "s16le",
+        "-acodec", "pcm_s16le",
+        "pipe:1",
+    ];
+
+    let pcm = run_ffmpeg_output(app, &args, "decode audio").await?;
+
+    let samples: Vec<i16> = pcm
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+
+    Ok(Some(compute_peaks(&samples, channels)))
+}
+
+/// Build min/max peak arrays per channel at every resolution level
+pub fn compute_peaks(samples: &[i16], channels: u32) -> WaveformPeaks {
+    let channel_count = channels.max(1) as usize;
+    let frames = samples.len() / channel_count;
+
+    let levels = PEAK_LEVELS
+        .iter()
+        .map(|&samples_per_peak| {
+            let data = (0..channel_count)
+                .map(|channel| channel_peaks(samples, channel_count, channel, frames, samples_per_peak))
+                .collect();
+            PeakLevel {
+                samples_per_peak,
+                data,
+            }
+        })
+        .collect();
+
+    WaveformPeaks {
+        sample_rate: PEAKS_SAMPLE_RATE,
+        channels: channel_count as u32,
+        duration: frames as f64 / PEAKS_SAMPLE_RATE as f64,
+        levels,
+    }
+}
+
+/// Min/max pairs for one channel, scaled from i16 to the i8 range to keep the payload small
+fn channel_peaks(
+    samples: &[i16],
+    channel_count: usize,
+    channel: usize,
+    frames: usize,
+    samples_per_peak: u32,
+) -> Vec<i8> {
+    let window = samples_per_peak as usize;
+    let mut peaks = Vec::with_capacity((frames / window + 1) * 2);
+
+    let mut start = 0;
+    while start < frames {
+        let end = (start + window).min(frames);
+        let mut min = i16::MAX;
+        let mut max = i16::MIN;
+        for frame in start..end {
+            let sample = samples[frame * channel_count + channel];
+            min = min.min(sample);
+            max = max.max(sample);
+        }
+        peaks.push((min >> 8) as i8);
+        peaks.push((max >> 8) as i8);
+        start = end;
+    }
+
+    peaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_min_max_pairs_per_channel_and_window() {
+        // 300 stereo frames: a loud first window on the left, quiet audio on the right
+        let mut samples = Vec::new();
+        for frame in 0..300 {
+            let left = match frame {
+                10 => i16::MAX,
+                20 => i16::MIN,
+                _ => 512,
+            };
+            samples.extend([left, -256]);
+        }
+
+        let peaks = compute_peaks(&samples, 2);
+        assert_eq!(peaks.channels, 2);
+        assert_eq!(peaks.sample_rate, PEAKS_SAMPLE_RATE);
+        assert_eq!(peaks.duration, 300.0 / PEAKS_SAMPLE_RATE as f64);
+
+        let finest = &peaks.levels[0];
+        assert_eq!(finest.samples_per_peak, 256);
+        // Windows of 256 and 44 frames
+        assert_eq!(finest.data[0], vec![-128, 127, 2, 2]);
+        assert_eq!(finest.data[1], vec![-1, -1, -1, -1]);
+
+        let coarsest = peaks.levels.last().unwrap();
+        assert_eq!(coarsest.data[0], vec![-128, 127]);
+    }
+
+    #[test]
+    fn has_every_level_from_finest_to_coarsest() {
+        let peaks = compute_peaks(&[0; 20000], 1);
+        let sizes: Vec<u32> = peaks.levels.iter().map(|l| l.samples_per_peak).collect();
+        assert_eq!(sizes, PEAK_LEVELS.to_vec());
+        let lengths: Vec<usize> = peaks.levels.iter().map(|l| l.data[0].len()).collect();
+        // ceil(20000 / window) min/max pairs
+        assert_eq!(lengths, vec![158, 40, 10, 4]);
+    }
+
+    #[test]
+    fn treats_zero_channels_as_mono_and_drops_partial_frames() {
+        let peaks = compute_peaks(&[100, 200, 300], 0);
+        assert_eq!(peaks.channels, 1);
+        assert_eq!(peaks.levels[0].data.len(), 1);
+
+        let stereo = compute_peaks(&[100, 200, 300], 2);
+        assert_eq!(stereo.duration, 1.0 / PEAKS_SAMPLE_RATE as f64);
+    }
+
+    #[test]
+    fn silence_produces_no_peaks() {
+        let peaks = compute_peaks(&[], 2);
+        assert_eq!(peaks.duration, 0.0);
+        assert!(peaks
+            .levels
+            .iter()
+            .all(|level| level.data.iter().all(Vec::is_empty)));
+    }
+}
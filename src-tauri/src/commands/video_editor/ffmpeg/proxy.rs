@@ -0,0 +1,77 @@
+use tauri::AppHandle;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use crate::commands::video_editor::ffmpeg::ffmpeg::run_ffmpeg;
+
+/// Proxy height in pixels; width follows the source aspect ratio
+const PROXY_HEIGHT: u32 = 540;
+const PROXY_CRF: &str = "28";
+const PROXY_AUDIO_BITRATE: &str = "96k";
+
+/// Proxies currently being encoded, so repeated requests don't start duplicate jobs
+static IN_FLIGHT: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Encode a low-res, all-intra proxy of `input` at `output`.
+///
+/// Every frame is a keyframe so seeking and trimming the proxy is cheap.
+/// The proxy is written to a temporary file and renamed on success, so a
+/// half-written proxy is never picked up. Returns `false` if the proxy already
+/// exists or is being generated by another task.
+pub async fn generate_proxy(app: &AppHandle, input: &str, output: &Path) -> Result<bool, String> {
+    if output.exists() {
+        return Ok(false);
+    }
+
+    {
+        let mut in_flight = IN_FLIGHT.lock().map_err(|_| "Proxy job registry poisoned")?;
+        if !in_flight.insert(output.to_path_buf()) {
+            return Ok(false);
+        }
+    }
+
+    let result = encode_proxy(app, input, output).await;
+
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        in_flight.remove(output);
+    }
+
+    result.map(|_| true)
+}
+
+async fn encode_proxy(app: &AppHandle, input: &str, output: &Path) -> Result<(), String> {
+    let temp_output = output.with_extension("tmp.mp4");
+    let temp_str = temp_output.to_str().ok_or("Invalid proxy path")?;
+    let scale = format!("scale=-2:{}", PROXY_HEIGHT);
+
+    let args = [
+        "-i",
+        input,
+        "-vf",
+        scale.as_str(),
+        "-c:v",
+        "libx264",
+        "-preset",
+        "veryfast",
+        "-crf",
+        PROXY_CRF,
+        "-g",
+        "1",
+        "-c:a",
+        "aac",
+        "-b:a",
+        PROXY_AUDIO_BITRATE,
+        "-movflags",
+        "+faststart",
+        "-y",
+        temp_str,
+    ];
+
+    if let Err(e) = run_ffmpeg(app, &args, "generate proxy").await {
+        let _ = std::fs::remove_file(&temp_output);
+        return Err(e);
+    }
+
+    std::fs::rename(&temp_output, output)
+        .map_err(|e| format!("Failed to finalize proxy: {}", e))
+}
@@ -0,0 +1,42 @@
+use tauri::AppHandle;
+use std::path::Path;
+use crate::commands::video_editor::ffmpeg::ffmpeg::run_ffmpeg;
+
+/// Containers an export can be written to, with the save dialog label of each.
+/// The H.264/AAC streams of a preview fit all of them without re-encoding.
+pub const EXPORT_FORMATS: [(&str, &str); 3] = [
+    ("mp4", "MP4 Video"),
+    ("mov", "QuickTime Movie"),
+    ("mkv", "Matroska Video"),
+];
+
+/// Normalized export format for `format` (e.g. ".MOV"), or `None` if it can't be written
+pub fn export_format(format: &str) -> Option<&'static str> {
+    let format = format.trim().trim_start_matches('.').to_lowercase();
+    EXPORT_FORMATS
+        .iter()
+        .map(|(extension, _)| *extension)
+        .find(|extension| *extension == format)
+}
+
+/// Copy the streams of `input` into the container given by `output`'s extension
+pub async fn remux(app: &AppHandle, input: &str, output: &Path) -> Result<(), String> {
+    let output_str = output.to_str().ok_or("Invalid output path")?;
+
+    let args = [
+        "-i",
+        input,
+        "-map",
+        "0",
+        "-c",
+        "copy",
+        "-y",
+        output_str,
+    ];
+
+    let result = run_ffmpeg(app, &args, "remux video").await;
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
@@ -0,0 +1,224 @@
+//! Shared import pipeline for single, batch and folder imports
+//!
+//! Each file is hashed, probed and copied (or referenced) into an
+//! `ImportedVideoMeta` record. Records are returned to the caller, which
+//! persists them in one metadata write once every file is processed.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::commands::projects::filesystem::{
+    collect_files, current_timestamp, file_fingerprint, hash_file,
+};
+use crate::commands::projects::paths::ProjectPaths;
+use crate::commands::projects::types::{ImportedVideoMeta, ProjectMeta};
+use crate::commands::video_editor::ffmpeg::probe_video;
+
+/// File extensions picked up by folder imports when none are given
+pub const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "mkv", "mov", "avi", "webm"];
+
+pub enum ImportOutcome {
+    Imported(ImportedVideoMeta),
+    /// Content matches an existing or in-progress import with this id
+    Duplicate(String),
+}
+
+/// Settings and shared state for one import run
+pub struct ImportContext {
+    pub project_root: PathBuf,
+    pub reference: bool,
+    pub allow_duplicates: bool,
+    /// Content hash to video id for the project's imports and files claimed by this run
+    known_hashes: Mutex<HashMap<String, String>>,
+}
+
+impl ImportContext {
+    pub fn new(
+        project_root: PathBuf,
+        meta: &ProjectMeta,
+        reference: bool,
+        allow_duplicates: bool,
+    ) -> Self {
+        let known_hashes = meta
+            .imported_videos
+            .iter()
+            .filter(|r| Path::new(&r.file_path).exists())
+            .map(|r| (r.hash.clone(), r.id.clone()))
+            .collect();
+
+        Self {
+            project_root,
+            reference,
+            allow_duplicates,
+            known_hashes: Mutex::new(known_hashes),
+        }
+    }
+
+    /// Claim `hash` for `video_id`, or return the id that already holds it
+    fn claim(&self, hash: &str, video_id: &str) -> Result<Option<String>, String> {
+        let mut known = self
+            .known_hashes
+            .lock()
+            .map_err(|_| "Import registry poisoned")?;
+        if let Some(existing) = known.get(hash) {
+            if !self.allow_duplicates {
+                return Ok(Some(existing.clone()));
+            }
+        } else {
+            known.insert(hash.to_string(), video_id.to_string());
+        }
+        Ok(None)
+    }
+
+    fn release(&self, hash: &str, video_id: &str) {
+        if let Ok(mut known) = self.known_hashes.lock() {
+            if known.get(hash).map(String::as_str) == Some(video_id) {
+                known.remove(hash);
+            }
+        }
+    }
+}
+
+/// Parse the `mode` argument of the import commands; returns whether to import by reference
+pub fn parse_import_mode(mode: Option<&str>) -> Result<bool, String> {
+    match mode {
+        None | Some("copy") => Ok(false),
+        Some("reference") => Ok(true),
+        Some(other) => Err(format!("Unknown import mode '{}'", other)),
+    }
+}
+
+/// Run blocking filesystem work (hashing, copying) off the async runtime
+async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+/// Hash, probe and copy (or reference) a single file
+pub async fn import_file(
+    app: &AppHandle,
+    context: &ImportContext,
+    source_path: &Path,
+) -> Result<ImportOutcome, String> {
+    // Generate unique ID for the imported video
+    let video_id = format!("imported_{}", Uuid::new_v4().to_string().replace("-", ""));
+
+    // Look for an existing import with the same content before copying anything
+    let source = source_path.to_path_buf();
+    let hash = run_blocking(move || hash_file(&source)).await?;
+    if let Some(existing) = context.claim(&hash, &video_id)? {
+        return Ok(ImportOutcome::Duplicate(existing));
+    }
+
+    let result = copy_and_record(app, context, source_path, &video_id, &hash).await;
+    if result.is_err() {
+        context.release(&hash, &video_id);
+    }
+    result.map(ImportOutcome::Imported)
+}
+
+async fn copy_and_record(
+    app: &AppHandle,
+    context: &ImportContext,
+    source_path: &Path,
+    video_id: &str,
+    hash: &str,
+) -> Result<ImportedVideoMeta, String> {
+    // Read duration and stream information using FFprobe
+    let probe = probe_video(app, source_path.to_str().unwrap_or("")).await?;
+
+    let dest_path = if context.reference {
+        let source = source_path
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve video path: {}", e))?;
+
+        // Referenced files live outside the workspace, so let the webview load them
+        app.asset_protocol_scope()
+            .allow_file(&source)
+            .map_err(|e| format!("Failed to allow access to video file: {}", e))?;
+
+        source
+    } else {
+        let paths = ProjectPaths::new(&context.project_root);
+
+        // Ensure videos directory exists
+        std::fs::create_dir_all(paths.videos_dir())
+            .map_err(|e| format!("Failed to create videos directory: {}", e))?;
+
+        // Copy file to project videos directory
+        let source = source_path.to_path_buf();
+        let dest_path = paths.video_file(video_id);
+        let copy_dest = dest_path.clone();
+        run_blocking(move || {
+            std::fs::copy(&source, &copy_dest)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to copy video file: {}", e))
+        })
+        .await?;
+
+        dest_path
+    };
+
+    let (size, modified_at) = file_fingerprint(&dest_path)?;
+
+    // Keep the original filename so listings can show it
+    let original_name = source_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Imported Video")
+        .to_string();
+
+    Ok(ImportedVideoMeta {
+        id: video_id.to_string(),
+        original_name,
+        file_path: dest_path.to_string_lossy().to_string(),
+        reference: context.reference,
+        size,
+        modified_at,
+        hash: hash.to_string(),
+        probe,
+        imported_at: current_timestamp(),
+        tags: Vec::new(),
+    })
+}
+
+/// Video files in `folder`, optionally recursing, filtered by extension (case-insensitive)
+pub fn collect_folder_videos(
+    folder: &Path,
+    recursive: bool,
+    extensions: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    if recursive {
+        collect_files(folder, &mut files)?;
+    } else {
+        let entries = std::fs::read_dir(folder)
+            .map_err(|e| format!("Failed to read {}: {}", folder.display(), e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.retain(|file| {
+        file.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| {
+                extensions
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+            })
+    });
+    files.sort();
+    Ok(files)
+}
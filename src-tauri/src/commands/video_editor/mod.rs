@@ -1,5 +1,8 @@
+pub mod assembly;
 pub mod commands;
 pub mod ffmpeg;
+pub mod import;
+pub mod timeline;
 pub mod types;
 
 // Re-export commands
@@ -0,0 +1,176 @@
+//! Helpers for manipulating timeline clips on the backend
+//!
+//! These mirror the editing operations in the frontend's `useEditorState` hook:
+//! `trim_start`/`trim_end` are absolute times in the source video, and
+//! `position` is the clip's start on the timeline.
+
+use uuid::Uuid;
+
+use crate::commands::video_editor::types::TimelineClip;
+
+/// Shortest segment kept when splitting a clip; shorter pieces are merged into their neighbour
+const MIN_SEGMENT_DURATION: f64 = 0.5;
+
+/// Generate a clip id in the same `clip-<uuid>` form the frontend uses
+pub fn new_clip_id() -> String {
+    format!("clip-{}", Uuid::new_v4())
+}
+
+/// Split a clip at the given source times, dropping cuts outside its trimmed range
+pub fn split_clip_at(clip: &TimelineClip, cut_times: &[f64]) -> Vec<TimelineClip> {
+    let mut boundaries: Vec<f64> = cut_times
+        .iter()
+        .copied()
+        .filter(|t| *t > clip.trim_start && *t < clip.trim_end)
+        .collect();
+    boundaries.sort_by(|a, b| a.total_cmp(b));
+
+    let mut segments: Vec<(f64, f64)> = Vec::new();
+    let mut segment_start = clip.trim_start;
+    for boundary in boundaries {
+        if boundary - segment_start < MIN_SEGMENT_DURATION {
+            continue;
+        }
+        segments.push((segment_start, boundary));
+        segment_start = boundary;
+    }
+
+    match segments.last_mut() {
+        Some(last) if clip.trim_end - segment_start < MIN_SEGMENT_DURATION => {
+            last.1 = clip.trim_end;
+        }
+        _ => segments.push((segment_start, clip.trim_end)),
+    }
+
+    if segments.len() == 1 {
+        return vec![clip.clone()];
+    }
+
+    // The original transition leads out of the clip, so only the last segment keeps it
+    let last_index = segments.len() - 1;
+    let mut position = clip.position;
+    segments
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            let is_last = index == last_index;
+            let segment = TimelineClip {
+                id: new_clip_id(),
+                position,
+                trim_start: start,
+                trim_end: end,
+                duration: end - start,
+                transition_type: if is_last {
+                    clip.transition_type.clone()
+                } else {
+                    None
+                },
+                transition_duration: if is_last {
+                    clip.transition_duration
+                } else {
+                    None
+                },
+                ..clip.clone()
+            };
+            position += segment.duration;
+            segment
+        })
+        .collect()
+}
+
+/// Lay clips end to end starting at zero
+pub fn recompute_positions(clips: &mut [TimelineClip]) {
+    let mut position = 0.0;
+    for clip in clips.iter_mut() {
+        clip.position = position;
+        position += clip.duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(position: f64, trim_start: f64, trim_end: f64) -> TimelineClip {
+        TimelineClip {
+            id: "clip-original".to_string(),
+            name: "Scene 1".to_string(),
+            video_path: "videos/video_1.mp4".to_string(),
+            thumbnail: None,
+            original_duration: 20.0,
+            created_at: 0,
+            position,
+            trim_start,
+            trim_end,
+            duration: trim_end - trim_start,
+            transition_type: Some("fade".to_string()),
+            transition_duration: Some(1.0),
+        }
+    }
+
+    fn ranges(clips: &[TimelineClip]) -> Vec<(f64, f64)> {
+        clips.iter().map(|c| (c.trim_start, c.trim_end)).collect()
+    }
+
+    #[test]
+    fn splits_at_cuts_and_lays_segments_end_to_end() {
+        let segments = split_clip_at(&clip(10.0, 2.0, 12.0), &[8.0, 5.0]);
+
+        assert_eq!(ranges(&segments), vec![(2.0, 5.0), (5.0, 8.0), (8.0, 12.0)]);
+        let positions: Vec<f64> = segments.iter().map(|c| c.position).collect();
+        assert_eq!(positions, vec![10.0, 13.0, 16.0]);
+        assert!(segments
+            .iter()
+            .all(|c| c.duration == c.trim_end - c.trim_start));
+        assert!(segments
+            .iter()
+            .all(|c| c.id.starts_with("clip-") && c.id != "clip-original"));
+    }
+
+    #[test]
+    fn only_the_last_segment_keeps_the_transition() {
+        let segments = split_clip_at(&clip(0.0, 0.0, 10.0), &[4.0]);
+
+        assert_eq!(segments[0].transition_type, None);
+        assert_eq!(segments[0].transition_duration, None);
+        assert_eq!(segments[1].transition_type.as_deref(), Some("fade"));
+        assert_eq!(segments[1].transition_duration, Some(1.0));
+    }
+
+    #[test]
+    fn ignores_cuts_outside_the_trimmed_range() {
+        let original = clip(0.0, 2.0, 6.0);
+        let segments = split_clip_at(&original, &[0.0, 1.0, 2.0, 6.0, 9.0]);
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].id, original.id);
+    }
+
+    #[test]
+    fn merges_short_segments_into_their_neighbours() {
+        // 0.2s after the start is merged forward, 0.3s before the end backward
+        let segments = split_clip_at(&clip(0.0, 0.0, 10.0), &[0.2, 5.0, 9.7]);
+
+        assert_eq!(ranges(&segments), vec![(0.0, 5.0), (5.0, 10.0)]);
+    }
+
+    #[test]
+    fn cuts_closer_together_than_the_minimum_collapse_into_one() {
+        let segments = split_clip_at(&clip(0.0, 0.0, 10.0), &[4.0, 4.2, 4.4]);
+
+        assert_eq!(ranges(&segments), vec![(0.0, 4.0), (4.0, 10.0)]);
+    }
+
+    #[test]
+    fn recompute_positions_closes_gaps() {
+        let mut clips = vec![
+            clip(5.0, 0.0, 3.0),
+            clip(20.0, 1.0, 2.5),
+            clip(0.0, 0.0, 4.0),
+        ];
+        recompute_positions(&mut clips);
+
+        let positions: Vec<f64> = clips.iter().map(|c| c.position).collect();
+        assert_eq!(positions, vec![0.0, 3.0, 4.5]);
+    }
+}
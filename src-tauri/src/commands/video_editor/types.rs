@@ -20,7 +20,108 @@ pub struct TimelineClip {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EditorState {
+    /// Set when saving; the frontend does not need to send it
+    #[serde(default)]
+    pub schema_version: u32,
     pub clips: Vec<TimelineClip>,
     pub selected_clip_id: Option<String>,
     pub preview_video_path: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeakLevel {
+    pub samples_per_peak: u32,
+    /// Per channel: interleaved [min, max] pairs scaled to the i8 range
+    pub data: Vec<Vec<i8>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformPeaks {
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub duration: f64,
+    pub levels: Vec<PeakLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneCut {
+    /// Cut time in seconds, in the source video's timeline
+    pub time: f64,
+    /// Scene-change score between 0 and 1
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneDetectionResult {
+    pub cuts: Vec<SceneCut>,
+    /// Clips that replaced the analysed clip when split onto the timeline
+    pub clips: Vec<TimelineClip>,
+    /// Storyboard scenes created when split into the storyboard
+    pub scene_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadAirRange {
+    /// "silence", "black" or "freeze"
+    pub kind: String,
+    /// Range start in seconds, in the source video's timeline
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipDeadAirReport {
+    pub clip_id: String,
+    pub ranges: Vec<DeadAirRange>,
+    /// Trim points that drop dead air at the head and tail of the clip
+    pub suggested_trim_start: f64,
+    pub suggested_trim_end: f64,
+    /// Whether the suggested trims were applied to the editor state
+    pub trimmed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSkip {
+    pub path: String,
+    pub reason: String,
+    pub existing_video_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    /// Same shape as `import_video`'s result, one per imported file
+    pub imported: Vec<serde_json::Value>,
+    pub skipped: Vec<ImportSkip>,
+    pub failed: Vec<ImportFailure>,
+}
+
+/// A storyboard scene that has no downloaded video to place on the timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingScene {
+    pub scene_number: i32,
+    pub scene_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryboardAssembly {
+    pub state: EditorState,
+    pub missing_scenes: Vec<MissingScene>,
+}
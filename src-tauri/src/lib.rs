@@ -12,6 +12,11 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_keyring::init())
         .plugin(tauri_plugin_fs::init())
+        .setup(|app| {
+            projects::workspaces::allow_registered_workspaces(app.handle())?;
+            projects::recycle::purge_expired_everywhere(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // api key commands
             save_api_key,
@@ -20,13 +25,43 @@ pub fn run() {
             // workspace & projects commands
             get_workspace_dir,
             ensure_workspace_exists,
+            list_workspaces,
+            add_workspace,
+            remove_workspace,
+            switch_workspace,
             ensure_dir_exists,
             list_projects,
             create_project,
             delete_project,
+            rename_project,
+            duplicate_project,
+            list_recycled_items,
+            restore_recycled_item,
+            purge_recycled_items,
+            get_recycle_retention,
+            set_recycle_retention,
+            list_project_templates,
+            save_project_as_template,
+            delete_project_template,
+            get_project_defaults,
+            set_project_defaults,
+            export_project_archive,
+            import_project_archive,
             get_project,
+            list_recovered_files,
+            validate_project,
+            repair_project,
+            project_storage_report,
+            cleanup_project_storage,
             add_videos_to_project,
+            timeline_clips_using_video,
             delete_video_from_project,
+            find_duplicate_media,
+            set_video_tags,
+            query_library,
+            get_remix_lineage,
+            update_video_review,
+            select_take,
             // image commands
             save_image,
             get_image,
@@ -40,16 +75,37 @@ pub fn run() {
             write_scene,
             delete_scene,
             reorder_scenes,
+            list_history,
+            undo,
+            redo,
+            restore_history,
+            save_version,
+            list_versions,
+            delete_version,
+            diff_versions,
+            restore_version,
+            fork_version,
             // video editor commands
             create_preview_video,
             save_editor_state,
             load_editor_state,
+            assemble_rough_cut,
+            assemble_timeline_from_storyboard,
             export_video,
             generate_clip_waveform,
+            generate_clip_peaks,
             generate_clip_sprite,
+            detect_scenes,
+            analyze_dead_air,
+            set_use_proxies,
+            generate_proxies,
             import_video,
+            import_videos,
             list_imported_videos,
-            delete_imported_video
+            set_imported_video_tags,
+            delete_imported_video,
+            find_missing_media,
+            relink_media
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -33,12 +33,12 @@ export function Sidebar({
 }) {
   const navigate = useNavigate()
   const location = useLocation()
-  const { projects, deleteProject } = useProjects()
+  const { projects, deleteProject, selectProject } = useProjects()
   const [isProjectsExpanded, setIsProjectsExpanded] = useState(true)
 
   const onDelete = async (name: string) => {
     try {
-      await deleteProject(name, "trash")
+      await deleteProject(name)
     } catch (error) {
       const errMsg = error instanceof Error ? error.message : String(error)
       logError(`Failed to delete project: ${errMsg}`)
@@ -77,7 +77,14 @@ export function Sidebar({
                       <SidebarMenuButton
                         className="gap-2 px-2 py-1.5 group"
                         isActive={location.pathname === `/projects/${p.name}`}
-                        onClick={() => {
+                        onClick={async () => {
+                          try {
+                            await selectProject(p)
+                          } catch (error) {
+                            const errMsg = error instanceof Error ? error.message : String(error)
+                            toast.error("Failed to open project", { description: errMsg })
+                            return
+                          }
                           onSelectProject?.(p)
                           navigate(`/projects/${p.name}`, {
                             state: { project: p }
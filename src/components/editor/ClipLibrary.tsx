@@ -10,14 +10,16 @@ import { convertFileSrc } from "@tauri-apps/api/core";
 import { toast } from "sonner";
 import { cn } from "@/lib/utils";
 import type { VideoClip } from "@/types/video-editor";
+import { timelineClipsUsingVideo } from "@/hooks/tauri/use-projects";
+import type { ImportVideoOptions, ImportVideoResult } from "@/hooks/tauri/use-editor";
 
 interface ClipLibraryProps {
   clips: VideoClip[];
   onClipAdd?: (clip: VideoClip) => void;
   projectName: string;
-  onImportVideo?: () => Promise<VideoClip>;
+  onImportVideo?: (options?: ImportVideoOptions) => Promise<ImportVideoResult>;
   onLoadImportedVideos?: () => Promise<VideoClip[]>;
-  onDeleteImportedVideo?: (videoId: string) => Promise<void>;
+  onDeleteImportedVideo?: (videoId: string, removeClips?: boolean) => Promise<string[]>;
 }
 
 interface ClipItemProps {
@@ -128,16 +130,37 @@ export function ClipLibrary({ clips, onClipAdd, projectName, onImportVideo, onLo
     loadImportedVideos();
   }, [projectName, onLoadImportedVideos]);
 
-  const handleFilePicker = () => {
+  const handleFilePicker = (options?: ImportVideoOptions) => {
     if (!onImportVideo) {
       toast.error("Import functionality not available");
       return;
     }
 
-    // This will open the Tauri file dialog
-    onImportVideo().then((clip) => {
-      setImportedClips((prev) => [...prev, clip]);
-      toast.success("Video imported successfully");
+    // Without a file path this opens the Tauri file dialog
+    onImportVideo(options).then(({ clip, duplicate, sourcePath }) => {
+      if (!duplicate) {
+        setImportedClips((prev) => [...prev, clip]);
+        toast.success("Video imported successfully");
+        return;
+      }
+
+      // The project already has this video; reuse it unless asked to import a copy
+      toast.info("This video is already imported", {
+        description: `It matches "${clip.name}" in your imports.`,
+        action: {
+          label: "Use existing",
+          onClick: () => {
+            setImportedClips((prev) => prev.some((c) => c.id === clip.id) ? prev : [...prev, clip]);
+            setActiveTab("import");
+          },
+        },
+        cancel: sourcePath
+          ? {
+              label: "Import anyway",
+              onClick: () => handleFilePicker({ filePath: sourcePath, onDuplicate: "import" }),
+            }
+          : undefined,
+      });
     }).catch((error) => {
       const errMsg = error instanceof Error ? error.message : String(error);
       if (!errMsg.includes("cancelled")) {
@@ -146,16 +169,33 @@ export function ClipLibrary({ clips, onClipAdd, projectName, onImportVideo, onLo
     });
   };
 
-  const handleDeleteImported = async (videoId: string) => {
+  const handleDeleteImported = async (videoId: string, removeClips?: boolean) => {
     if (!onDeleteImportedVideo) {
       toast.error("Delete functionality not available");
       return;
     }
 
     try {
-      await onDeleteImportedVideo(videoId);
+      if (removeClips === undefined) {
+        const usedBy = await timelineClipsUsingVideo(projectName, videoId);
+        if (usedBy.length > 0) {
+          // Let the user choose what happens to the clips using the video
+          toast.warning("Video is used on the timeline", {
+            description: `Used by ${usedBy.map((clip) => clip.name).join(", ")}`,
+            action: { label: "Remove clips", onClick: () => { handleDeleteImported(videoId, true); } },
+            cancel: { label: "Keep clips", onClick: () => { handleDeleteImported(videoId, false); } },
+          });
+          return;
+        }
+      }
+
+      const removedClipIds = await onDeleteImportedVideo(videoId, removeClips);
       setImportedClips((prev) => prev.filter((clip) => clip.id !== videoId));
-      toast.success("Video removed");
+      toast.success("Video removed", {
+        description: removedClipIds.length > 0
+          ? `Removed ${removedClipIds.length} clip(s) from the timeline`
+          : undefined,
+      });
     } catch (error) {
       const errMsg = error instanceof Error ? error.message : String(error);
       toast.error("Failed to delete video", { description: errMsg });
@@ -189,7 +229,7 @@ export function ClipLibrary({ clips, onClipAdd, projectName, onImportVideo, onLo
               <Button
                 variant="outline"
                 size="sm"
-                onClick={handleFilePicker}
+                onClick={() => handleFilePicker()}
                 className="h-7 px-2 text-xs"
               >
                 <Upload className="size-3 mr-1" />
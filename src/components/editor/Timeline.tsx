@@ -3,7 +3,7 @@ import { Card, CardHeader, CardTitle, CardContent } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
 import { ScrollArea, ScrollBar } from "@/components/ui/scroll-area";
 import { Slider } from "@/components/ui/slider";
-import { Trash2, Scissors, Download, Loader2 } from "lucide-react";
+import { Trash2, Scissors, Download, Loader2, Undo2, Redo2 } from "lucide-react";
 import { TimelineClip } from "./TimelineClip";
 import { TransitionSelector, type TransitionConfig } from "./TransitionSelector";
 import type { TimelineClip as TimelineClipType } from "@/types/video-editor";
@@ -42,13 +42,15 @@ interface TimelineProps {
   currentTime?: number; // Current playback position in timeline seconds
   onTimelineClick?: (time: number) => void;
   onExport?: () => void;
+  onUndo?: () => void;
+  onRedo?: () => void;
   isExporting?: boolean;
   canExport?: boolean;
   projectName: string;
 }
 
 export const Timeline = memo(function Timeline({
-  clips, selectedClipId, onClipSelect, onClipDelete, onClipSplit, onClipReorder, onClipTransitionChange, currentTime, onTimelineClick, onExport, isExporting, canExport, projectName,
+  clips, selectedClipId, onClipSelect, onClipDelete, onClipSplit, onClipReorder, onClipTransitionChange, currentTime, onTimelineClick, onExport, onUndo, onRedo, isExporting, canExport, projectName,
 }: TimelineProps) {
   const [isDragging, setIsDragging] = useState(false);
   const [zoomLevel, setZoomLevel] = useState(DEFAULT_ZOOM);
@@ -187,6 +189,16 @@ export const Timeline = memo(function Timeline({
                 {zoomLevel}%
               </span>
             </div>
+            {onUndo && (
+              <Button size="sm" variant="outline" onClick={onUndo} title="Undo (Ctrl+Z)">
+                <Undo2 className="h-4 w-4" />
+              </Button>
+            )}
+            {onRedo && (
+              <Button size="sm" variant="outline" onClick={onRedo} title="Redo (Ctrl+Shift+Z)">
+                <Redo2 className="h-4 w-4" />
+              </Button>
+            )}
             <Button
               size="sm"
               variant="outline"
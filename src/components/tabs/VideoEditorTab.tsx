@@ -4,7 +4,7 @@ import { VideoPreview } from "@/components/editor/VideoPreview";
 import { ClipLibrary } from "@/components/editor/ClipLibrary";
 import { Timeline } from "@/components/editor/Timeline";
 import { useEditorState } from "@/hooks/tauri/use-editor-state";
-import { useEditor } from "@/hooks/tauri/use-editor";
+import { useEditor, type ImportVideoOptions, type ImportVideoResult } from "@/hooks/tauri/use-editor";
 import { useProjects } from "@/hooks/tauri/use-projects";
 import { toast } from "sonner";
 import type { VideoClip } from "@/types/video-editor";
@@ -32,7 +32,10 @@ export function VideoEditorTab({ projectName }: VideoEditorTabProps) {
     selectClip,
     splitClip,
     reorderClips,
-    updateClipTransition
+    updateClipTransition,
+    undo,
+    redo,
+    reload: reloadEditorState
   } = useEditorState(projectName, previewVideoPath);
   const { createPreviewVideo, exportVideo, importVideo, listImportedVideos, deleteImportedVideo } = useEditor();
 
@@ -79,18 +82,64 @@ export function VideoEditorTab({ projectName }: VideoEditorTabProps) {
     toast.success('Clip added to timeline');
   };
 
-  const handleImportVideo = async (): Promise<VideoClip> => {
-    return await importVideo(projectName);
+  const handleImportVideo = async (options?: ImportVideoOptions): Promise<ImportVideoResult> => {
+    return await importVideo(projectName, options);
   };
 
   const handleLoadImportedVideos = async (): Promise<VideoClip[]> => {
     return await listImportedVideos(projectName);
   };
 
-  const handleDeleteImportedVideo = async (videoId: string): Promise<void> => {
-    await deleteImportedVideo(projectName, videoId);
+  const handleDeleteImportedVideo = async (videoId: string, removeClips?: boolean): Promise<string[]> => {
+    const removedClipIds = await deleteImportedVideo(projectName, videoId, removeClips);
+    if (removedClipIds.length > 0) {
+      // The backend edited the saved timeline; load it before the next auto-save
+      await reloadEditorState();
+    }
+    return removedClipIds;
   };
 
+  const handleUndo = useCallback(async () => {
+    try {
+      const label = await undo();
+      toast.success(label ? `Undone: ${label}` : 'Nothing to undo');
+    } catch (error) {
+      toast.error('Failed to undo', { description: error instanceof Error ? error.message : String(error) });
+    }
+  }, [undo]);
+
+  const handleRedo = useCallback(async () => {
+    try {
+      const label = await redo();
+      toast.success(label ? `Redone: ${label}` : 'Nothing to redo');
+    } catch (error) {
+      toast.error('Failed to redo', { description: error instanceof Error ? error.message : String(error) });
+    }
+  }, [redo]);
+
+  // Ctrl/Cmd+Z to undo, Ctrl/Cmd+Shift+Z or Ctrl+Y to redo, outside text fields
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      const target = e.target as HTMLElement | null;
+      if (target && (target.tagName === 'INPUT' || target.tagName === 'TEXTAREA' || target.isContentEditable)) {
+        return;
+      }
+      if (!(e.ctrlKey || e.metaKey)) return;
+
+      const key = e.key.toLowerCase();
+      if (key === 'z' && !e.shiftKey) {
+        e.preventDefault();
+        handleUndo();
+      } else if ((key === 'z' && e.shiftKey) || key === 'y') {
+        e.preventDefault();
+        handleRedo();
+      }
+    };
+
+    window.addEventListener('keydown', handleKeyDown);
+    return () => window.removeEventListener('keydown', handleKeyDown);
+  }, [handleUndo, handleRedo]);
+
   const handleDelete = () => {
     if (selectedClipId) {
       removeClip(selectedClipId);
@@ -121,7 +170,7 @@ export function VideoEditorTab({ projectName }: VideoEditorTabProps) {
 
     setIsExporting(true);
     try {
-      const exportPath = await exportVideo(previewVideoPath);
+      const exportPath = await exportVideo(previewVideoPath, projectName, clips);
       toast.success('Video exported successfully', {
         description: exportPath,
       });
@@ -221,6 +270,8 @@ export function VideoEditorTab({ projectName }: VideoEditorTabProps) {
             currentTime={currentPlaybackTime ?? undefined}
             onTimelineClick={handleTimelineClick}
             onExport={handleExport}
+            onUndo={handleUndo}
+            onRedo={handleRedo}
             isExporting={isExporting}
             canExport={!!previewVideoPath && !isGeneratingPreview}
             projectName={projectName}
@@ -2,7 +2,7 @@ import { useState, useEffect, useCallback, useMemo } from "react";
 import { VideoPlayer } from "@/components/videos/VideoPlayer";
 import { VideoGallery } from "@/components/videos/VideoGallery";
 import { VideoDetails } from "@/components/videos/VideoDetails";
-import { useProjects, type VideoMeta, type ProjectMeta } from "@/hooks/tauri/use-projects";
+import { useProjects, timelineClipsUsingVideo, type VideoMeta, type ProjectMeta } from "@/hooks/tauri/use-projects";
 import { useVideos } from "@/hooks/use-videos";
 import { useVideoStatusStore } from "@/stores/useVideoStatusStore";
 import { toast } from "sonner";
@@ -78,23 +78,39 @@ export function VideosTab({ projectName }: VideosTabProps) {
     setSelectedVideoId(video.id);
   }, []);
 
-  const handleVideoDelete = useCallback(async (videoId: string) => {
+  const handleVideoDelete = useCallback(async (videoId: string, removeClips?: boolean) => {
     if (!projectName) return;
     try {
-      await deleteVideoFromProject(projectName, videoId);
+      if (removeClips === undefined) {
+        const usedBy = await timelineClipsUsingVideo(projectName, videoId);
+        if (usedBy.length > 0) {
+          // Let the user choose what happens to the clips using the video
+          toast.warning("Video is used on the timeline", {
+            description: `Used by ${usedBy.map((clip) => clip.name).join(", ")}`,
+            action: { label: "Remove clips", onClick: () => { handleVideoDelete(videoId, true); } },
+            cancel: { label: "Keep clips", onClick: () => { handleVideoDelete(videoId, false); } },
+          });
+          return;
+        }
+      }
+
+      const removedClipIds = await deleteVideoFromProject(projectName, videoId, removeClips);
       const updatedMeta = await getProject(projectName);
       setProjectMeta(updatedMeta);
       setSelectedVideoId(null);
-      toast.success("Video deleted successfully");
+      toast.success("Video deleted successfully", {
+        description: removedClipIds.length > 0
+          ? `Removed ${removedClipIds.length} clip(s) from the timeline`
+          : undefined,
+      });
       
       // fire and forget
       deleteVideo(videoId).catch((error) => {
         console.error("Failed to delete video from OpenAI:", error);
       });
     } catch (error) {
-      toast.error("Failed to delete video", {
-        description: error instanceof Error ? error.message : String(error),
-      });
+      const description = error instanceof Error ? error.message : String(error);
+      toast.error("Failed to delete video", { description });
     }
   }, [projectName, deleteVideo, deleteVideoFromProject, getProject]);
 
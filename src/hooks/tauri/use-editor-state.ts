@@ -12,12 +12,11 @@ export function useEditorState(projectName: string, previewVideoPath: string | n
 
   const [isLoaded, setIsLoaded] = useState(false);
 
-  // Load saved state when component mounts or project changes
-  useEffect(() => {
+  const loadState = useCallback(() => {
     debug(`[EditorState] Loading state for project: ${projectName}`);
     setIsLoaded(false);
 
-    invoke<EditorState | null>('load_editor_state', { projectName })
+    return invoke<EditorState | null>('load_editor_state', { projectName })
       .then(savedState => {
         if (savedState) {
           setClips(savedState.clips);
@@ -35,6 +34,11 @@ export function useEditorState(projectName: string, previewVideoPath: string | n
       });
   }, [projectName]);
 
+  // Load saved state when component mounts or project changes
+  useEffect(() => {
+    loadState();
+  }, [loadState]);
+
   // Auto-save whenever state changes (after initial load)
   useEffect(() => {
     if (!isLoaded) return;
@@ -55,6 +59,20 @@ export function useEditorState(projectName: string, previewVideoPath: string | n
       });
   }, [clips, selectedClipId, previewVideoPath, projectName, isLoaded]);
 
+  // Undo or redo the latest timeline edit on disk, then load the result so the
+  // next auto-save doesn't write the old clips back. Resolves to the label of
+  // the edit, or null when there was nothing to undo or redo.
+  const applyHistory = useCallback(async (command: 'undo' | 'redo'): Promise<string | null> => {
+    const entry = await invoke<{ label: string } | null>(command, { projectName });
+    if (entry) {
+      await loadState();
+    }
+    return entry?.label ?? null;
+  }, [projectName, loadState]);
+
+  const undo = useCallback(() => applyHistory('undo'), [applyHistory]);
+  const redo = useCallback(() => applyHistory('redo'), [applyHistory]);
+
   // Calculate total duration accounting for transitions (transitions cause overlap)
   const totalDuration = useMemo(() => calculateTotalDuration(clips), [clips]);
 
@@ -159,5 +177,9 @@ export function useEditorState(projectName: string, previewVideoPath: string | n
     splitClip,
     reorderClips,
     updateClipTransition,
+    undo,
+    redo,
+    // Pick up timeline changes made by the backend, e.g. clips removed with their media
+    reload: loadState,
   };
 }
@@ -3,6 +3,19 @@ import { useCallback } from "react";
 import type { TimelineClip } from "@/types/video-editor";
 import type { VideoClip } from "@/types/video-editor";
 
+// When `duplicate` is set, `clip` is the asset already in the project and
+// `sourcePath` the picked file, to import anyway with `onDuplicate: "import"`
+export interface ImportVideoOptions {
+  filePath?: string;
+  onDuplicate?: "skip" | "import";
+}
+
+export interface ImportVideoResult {
+  clip: VideoClip;
+  duplicate: boolean;
+  sourcePath: string | null;
+}
+
 export function useEditor() {
   const createPreviewVideo = useCallback(
     async (
@@ -24,10 +37,16 @@ export function useEditor() {
   );
 
   const exportVideo = useCallback(
-    async (previewPath: string): Promise<string> => {
+    async (
+      previewPath: string,
+      projectName?: string,
+      clips?: TimelineClip[]
+    ): Promise<string> => {
       try {
         const exportPath = await invoke<string>("export_video", {
           previewPath,
+          projectName: projectName ?? null,
+          clips: clips ?? null,
         });
         return exportPath;
       } catch (error) {
@@ -39,18 +58,26 @@ export function useEditor() {
   );
 
   const importVideo = useCallback(
-    async (projectName: string): Promise<VideoClip> => {
+    async (
+      projectName: string,
+      options: ImportVideoOptions = {}
+    ): Promise<ImportVideoResult> => {
       try {
         const metadata = await invoke<any>("import_video", {
           projectName,
-          filePath: null,
+          filePath: options.filePath ?? null,
+          onDuplicate: options.onDuplicate ?? null,
         });
         return {
-          id: metadata.id,
-          name: metadata.name,
-          videoPath: metadata.videoPath,
-          originalDuration: metadata.originalDuration,
-          createdAt: metadata.createdAt,
+          clip: {
+            id: metadata.id,
+            name: metadata.name,
+            videoPath: metadata.videoPath,
+            originalDuration: metadata.originalDuration,
+            createdAt: metadata.createdAt,
+          },
+          duplicate: metadata.duplicate === true,
+          sourcePath: metadata.sourcePath ?? null,
         };
       } catch (error) {
         console.error("Failed to import video:", error);
@@ -81,12 +108,14 @@ export function useEditor() {
     []
   );
 
+  // Resolves to the ids of the timeline clips removed along with the video
   const deleteImportedVideo = useCallback(
-    async (projectName: string, videoId: string): Promise<void> => {
+    async (projectName: string, videoId: string, removeClips?: boolean): Promise<string[]> => {
       try {
-        await invoke("delete_imported_video", {
+        return await invoke<string[]>("delete_imported_video", {
           projectName,
           videoId,
+          removeClips,
         });
       } catch (error) {
         console.error("Failed to delete imported video:", error);
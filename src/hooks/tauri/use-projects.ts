@@ -1,10 +1,13 @@
 import { invoke } from "@tauri-apps/api/core";
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
+import type { TimelineClip } from "@/types/video-editor";
 
 export interface ProjectSummary {
   name: string;
   path: string;
   created_at: number;
+  // Workspace folder the project is in
+  workspace: string;
 }
 
 export interface VideoMeta {
@@ -42,17 +45,31 @@ const ensureWorkspaceExists = async (): Promise<void> => {
   await invoke("ensure_workspace_exists");
 };
 
-const listProjects = async (): Promise<ProjectSummary[]> => {
-  return await invoke<ProjectSummary[]>("list_projects");
+const listProjects = async (allWorkspaces?: boolean): Promise<ProjectSummary[]> => {
+  return await invoke<ProjectSummary[]>("list_projects", { allWorkspaces });
+};
+
+// Project commands open projects by name in the active workspace, so a project listed
+// from another workspace needs that workspace made active first. Resolves to whether it switched.
+const switchToProjectWorkspace = async (project: ProjectSummary): Promise<boolean> => {
+  const active = await getWorkspaceDir();
+  if (!project.workspace || project.workspace === active) {
+    return false;
+  }
+  await invoke("switch_workspace", { path: project.workspace });
+  return true;
 };
 
 const createProjectAPI = async (name: string): Promise<ProjectSummary> => {
   return await invoke<ProjectSummary>("create_project", { name });
 };
 
+// "recycle" moves the project to the workspace recycle area, where it can be restored
+export type DeleteProjectMode = "recycle" | "trash" | "delete";
+
 const deleteProjectAPI = async (
   name: string,
-  mode?: "trash" | "delete"
+  mode?: DeleteProjectMode
 ): Promise<void> => {
   await invoke("delete_project", { name, mode });
 };
@@ -65,8 +82,23 @@ const addVideosToProject = async (projectName: string, videosMeta: VideoMeta[]):
   await invoke("add_videos_to_project", { projectName, videosMeta });
 };
 
-const deleteVideoFromProject = async (projectName: string, videoId: string): Promise<void> => {
-  await invoke("delete_video_from_project", { projectName, videoId });
+// Deleting a video the timeline uses fails unless removeClips says what to do with its clips:
+// true removes them, false keeps them without media. Resolves to the ids of removed clips.
+// Check timelineClipsUsingVideo first to ask the user.
+const deleteVideoFromProject = async (
+  projectName: string,
+  videoId: string,
+  removeClips?: boolean
+): Promise<string[]> => {
+  return await invoke<string[]>("delete_video_from_project", { projectName, videoId, removeClips });
+};
+
+// Clips on the timeline that deleting a generated or imported video would leave without media
+export const timelineClipsUsingVideo = async (
+  projectName: string,
+  videoId: string
+): Promise<TimelineClip[]> => {
+  return await invoke<TimelineClip[]>("timeline_clips_using_video", { projectName, videoId });
 };
 
 const saveImage = async (
@@ -128,7 +160,7 @@ export function useProjects() {
 
   // Mutation for deleting a project
   const deleteProjectMutation = useMutation({
-    mutationFn: ({ name, mode }: { name: string; mode: "trash" | "delete" }) =>
+    mutationFn: ({ name, mode }: { name: string; mode: DeleteProjectMode }) =>
       deleteProjectAPI(name, mode),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: PROJECTS_QUERY_KEY });
@@ -144,9 +176,16 @@ export function useProjects() {
 
     // Mutations
     createProject: createProjectMutation.mutateAsync,
-    deleteProject: (name: string, mode: "trash" | "delete" = "trash") =>
+    deleteProject: (name: string, mode: DeleteProjectMode = "recycle") =>
       deleteProjectMutation.mutateAsync({ name, mode }),
 
+    // Call before opening a project, which may be in another workspace
+    selectProject: async (project: ProjectSummary) => {
+      if (await switchToProjectWorkspace(project)) {
+        await queryClient.invalidateQueries({ queryKey: PROJECTS_QUERY_KEY });
+      }
+    },
+
     // Non-reactive API calls
     ensureWorkspaceExists,
     getProject,